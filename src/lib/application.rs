@@ -1,67 +1,335 @@
 use crate::lib::{
-    flood, game::Game, gen_mine_grid::gen_mine_grid, message::Message, position::Position,
-    ui::build_ui, widget::Widget,
+    autosave::{take_autosave, write_autosave},
+    bindings::{is_cursor_key, Action, Bindings, ACTIONS},
+    bindings_dialog::bindings_dialog,
+    board::{Zoom, ZOOM_STEP},
+    custom_dialog::custom_dialog,
+    difficulty::{BEGINNER, DIFFICULTIES},
+    endless::Coord,
+    engine::Event,
+    file_dialog::{confirm_dialog, error_dialog, open_dialog, save_dialog},
+    first_click::FirstClick,
+    game::Game,
+    message::Message,
+    position::Position,
+    render::render,
+    seed_dialog::seed_dialog,
+    ui::build_ui,
+    widget::Widget,
+    zoom::{zoom_key, ZoomLevels},
+};
+use gdk::{keys::constants as key, ModifierType, ScrollDirection};
+use gio::prelude::*;
+use glib::ToVariant;
+use gtk::{prelude::*, ApplicationWindow};
+use std::{
+    cell::{Cell, RefCell},
+    fs,
+    rc::Rc,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
 };
-use gtk::prelude::*;
-use std::{cell::RefCell, rc::Rc};
 
 pub struct Application {
     pub widget: Rc<Widget>,
     game: Rc<RefCell<Game>>,
+    tenths: Rc<Cell<bool>>,
+    zooms: Rc<RefCell<ZoomLevels>>,
+    bindings: Rc<RefCell<Bindings>>,
 }
 
+/// Space the window needs around the board for its borders and the bars above and below it.
+const WINDOW_MARGIN: (i32, i32) = (48, 160);
+/// How far the pointer has to move with the button held before a press turns into panning.
+const PAN_THRESHOLD: f64 = 8.0;
+/// How often no-guess generation reports its progress; more often would crowd input out.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
 macro_rules! bind_game_resize {
-    ($tx:expr, $widget:expr, $game:expr, $action:expr, $size:expr) => {
-        if let Some(action) = $widget.menu_bar_actions.get($action) {
+    ($tx:expr, $widget:expr, $game:expr, $zooms:expr, $difficulty:expr) => {
+        let difficulty = $difficulty;
+        if let Some(action) = $widget.menu_bar_actions.get(difficulty.id) {
             let tx = $tx.clone();
             let widget = $widget.clone();
             let game = $game.clone();
+            let zooms = $zooms.clone();
             action.connect_activate(glib::clone!(@weak widget, @strong game => move |_, _| {
                 let game = game.clone();
-                let window = &widget.window;
 
-                if game.borrow().size == $size {
+                if game.borrow().difficulty() == Some(difficulty) {
                     return;
                 }
 
                 if let Ok(mut game) = game.try_borrow_mut() {
-                    game.size = $size;
-                    gen_mine_grid(&widget, tx.clone(), $size);
-
-                    window.resize(100, 100);
-
-                    tx.send(Message::Reset).expect("could not reset");
+                    let (width, height, mines) =
+                        (difficulty.width, difficulty.height, difficulty.mines);
+                    resize_board(
+                        &widget,
+                        &mut game,
+                        &zooms.borrow(),
+                        tx.clone(),
+                        width,
+                        height,
+                        mines,
+                    );
                 };
             }));
         }
     };
 }
 
+fn format_time(elapsed: Duration, tenths: bool) -> String {
+    let secs = elapsed.as_secs();
+
+    let time = if secs >= 3600 {
+        format!(
+            "{hours}:{minutes:0>2}:{seconds:0>2}",
+            hours = secs / 3600,
+            minutes = (secs / 60) % 60,
+            seconds = secs % 60,
+        )
+    } else {
+        format!(
+            "{minutes}:{seconds:0>2}",
+            minutes = secs / 60,
+            seconds = secs % 60,
+        )
+    };
+
+    if tenths {
+        format!("{}.{}", time, elapsed.subsec_millis() / 100)
+    } else {
+        time
+    }
+}
+
+/// The message for a bound action; actions on a cell need one to act on.
+fn action_message(action: Action, position: Option<Position>) -> Option<Message> {
+    let message = match (action, position) {
+        (Action::Reveal, Some(position)) => Message::UpdateButton(position, false),
+        (Action::Flag, Some(position)) => Message::UpdateButton(position, true),
+        (Action::Chord, Some(position)) => Message::Chord(position),
+        (Action::Question, Some(position)) => Message::Question(position),
+        (Action::NewGame, _) => Message::Reset,
+        (Action::Pause, _) => Message::Pause,
+        (Action::Undo, _) => Message::Undo,
+        (Action::Redo, _) => Message::Redo,
+        (Action::Reveal, None)
+        | (Action::Flag, None)
+        | (Action::Chord, None)
+        | (Action::Question, None) => return None,
+    };
+
+    Some(message)
+}
+
+/// Makes the keys bound to menu actions their accelerators.
+fn set_accels(application: &gtk::Application, bindings: &Bindings) {
+    for action in ACTIONS.iter() {
+        if let Some(id) = action.app_action() {
+            let accels = bindings.accels(*action);
+            let accels = accels.iter().map(String::as_str).collect::<Vec<&str>>();
+
+            application.set_accels_for_action(&format!("app.{}", id), &accels);
+        }
+    }
+}
+
+fn seed_label(game: &Game) -> String {
+    let mut label = format!("Seed: {}", game.seed);
+
+    if game.assisted {
+        label.push_str(" (assisted)");
+    }
+
+    if game.no_guess_failed {
+        label.push_str(" · no guess-free board found");
+    }
+
+    if let Some(endless) = &game.endless {
+        label.push_str(" · endless");
+
+        if game.state.is_over() {
+            label.push_str(&format!(" · {} cleared", endless.cleared));
+        }
+    }
+
+    if let Some(time) = game.final_time() {
+        label.push_str(&format!(
+            " · {}.{:0>3}s",
+            time.as_secs(),
+            time.subsec_millis()
+        ));
+    }
+
+    label
+}
+
+/// Room the board can take on the monitor showing the window.
+fn board_room(window: &ApplicationWindow) -> (i32, i32) {
+    let monitor = gdk::Display::get_default().and_then(|display| {
+        window
+            .get_window()
+            .and_then(|window| display.get_monitor_at_window(&window))
+            .or_else(|| display.get_primary_monitor())
+            .or_else(|| display.get_monitor(0))
+    });
+
+    let (width, height) = match monitor {
+        Some(monitor) => {
+            let area = monitor.get_workarea();
+            (area.width - WINDOW_MARGIN.0, area.height - WINDOW_MARGIN.1)
+        }
+        None => (800, 600),
+    };
+
+    (width.max(200), height.max(200))
+}
+
+/// The zoom a board opens at before the player picks one: full size, or smaller if that is
+/// what it takes to fit on the screen.
+fn default_zoom(widget: &Widget) -> f64 {
+    let (width, height) = board_room(&widget.window);
+
+    widget.board.fit_zoom(width, height).min(1.0)
+}
+
+/// Sizes the board for the game, at the zoom last used for its difficulty, and shrinks the
+/// window around it.
+fn show_board(widget: &Widget, game: &Game, zooms: &ZoomLevels) {
+    let (width, height) = board_room(&widget.window);
+
+    widget.board.resize(game.width, game.height);
+    widget.board.set_max_size(width, height);
+
+    let zoom = zooms
+        .get(zoom_key(game))
+        .unwrap_or_else(|| default_zoom(widget));
+    widget.board.set_zoom(zoom, None);
+
+    if let Some(endless) = widget.menu_bar_actions.get("endless") {
+        endless.set_state(&game.endless.is_some().to_variant());
+    }
+
+    widget.window.resize(100, 100);
+}
+
+/// Keeps the cell a move was made on in view on an endless board, wherever the move left it.
+fn follow(widget: &Widget, game: &Game, coord: Option<Coord>) {
+    let position = game
+        .endless
+        .as_ref()
+        .zip(coord)
+        .and_then(|(endless, coord)| endless.position(coord));
+
+    if let Some(position) = position {
+        widget.board.scroll_to(&position);
+    }
+}
+
+/// Moves on to a new generation of the board, cancelling any no-guess search still running for
+/// the last one, and returns its id along with the flag that cancels it in turn.
+fn next_generation(
+    generation: &Cell<u32>,
+    cancel: &RefCell<Arc<AtomicBool>>,
+) -> (u32, Arc<AtomicBool>) {
+    generation.set(generation.get() + 1);
+
+    let next = Arc::new(AtomicBool::new(false));
+    cancel.replace(next.clone()).store(true, Ordering::Relaxed);
+
+    (generation.get(), next)
+}
+
+fn resize_board(
+    widget: &Widget,
+    game: &mut Game,
+    zooms: &ZoomLevels,
+    tx: glib::Sender<Message>,
+    width: u16,
+    height: u16,
+    mines: u16,
+) {
+    game.width = width;
+    game.height = height;
+    game.mine_count = mines;
+    game.set_endless(false);
+    show_board(widget, game, zooms);
+
+    tx.send(Message::Reset).expect("could not reset");
+}
+
 impl Application {
-    pub fn new(app: &gtk::Application) -> Self {
+    pub fn new(app: &gtk::Application, seed: Option<u64>) -> Self {
         let (tx, rx): (glib::Sender<Message>, glib::Receiver<Message>) =
             glib::MainContext::channel(glib::PRIORITY_HIGH);
 
+        let mut game = Game::new();
+        if let Some(seed) = seed {
+            game.new_mines_from_seed(seed);
+        }
+
+        let bindings = Bindings::load();
+        set_accels(app, &bindings);
+
         let app = Application {
             widget: Rc::new(build_ui(app)),
-            game: Rc::new(RefCell::new(Game::new())),
+            game: Rc::new(RefCell::new(game)),
+            tenths: Rc::new(Cell::new(false)),
+            zooms: Rc::new(RefCell::new(ZoomLevels::load())),
+            bindings: Rc::new(RefCell::new(bindings)),
         };
-        app.update_main_ui_thread(rx);
+        app.update_main_ui_thread(rx, tx.clone());
 
         app.bind_menubar(tx.clone());
         app.setup_labels_and_reset(tx.clone());
         app.bind_clock(tx.clone());
+        app.bind_board(tx.clone());
+        app.bind_minimap();
 
-        gen_mine_grid(&app.widget.clone(), tx.clone(), 9);
+        show_board(&app.widget, &app.game.borrow(), &app.zooms.borrow());
+
+        if seed.is_none() {
+            app.offer_resume(tx.clone());
+        }
 
         app
     }
 
+    pub fn autosave(&self) {
+        if let Err(error) = write_autosave(&self.game.borrow()) {
+            eprintln!("could not save the game: {}", error);
+        }
+    }
+
+    fn offer_resume(&self, tx: glib::Sender<Message>) {
+        let widget = self.widget.clone();
+        let text = match take_autosave() {
+            Some(text) => text,
+            None => return,
+        };
+
+        if !confirm_dialog(&widget.window, "Resume the game from last time?") {
+            return;
+        }
+
+        if let Err(error) = self.game.borrow_mut().load(&text) {
+            eprintln!("could not resume the game: {}", error);
+            return;
+        }
+
+        show_board(&widget, &self.game.borrow(), &self.zooms.borrow());
+
+        tx.send(Message::Redraw).expect("could not redraw");
+    }
+
     fn bind_menubar(&self, tx: glib::Sender<Message>) {
         let widget = self.widget.clone();
         let window = &widget.window;
-        let mut w_mines = &widget.mines.clone();
-        let w_mines_grid = &widget.mines_grid;
 
         if let Some(quit) = widget.menu_bar_actions.get("quit") {
             quit.connect_activate(glib::clone!(@weak window => move |_, _| {
@@ -76,271 +344,672 @@ impl Application {
             }));
         }
 
-        {
-            bind_game_resize!(tx, widget, self.game, "game_1", 9);
-            bind_game_resize!(tx, widget, self.game, "game_2", 14);
-            bind_game_resize!(tx, widget, self.game, "game_3", 19);
+        let pause_tx = tx.clone();
+        if let Some(pause) = widget.menu_bar_actions.get("pause") {
+            pause.connect_activate(move |_, _| {
+                pause_tx.send(Message::Pause).expect("could not pause");
+            });
+        }
+
+        let undo_tx = tx.clone();
+        if let Some(undo) = widget.menu_bar_actions.get("undo") {
+            undo.connect_activate(move |_, _| {
+                undo_tx.send(Message::Undo).expect("could not undo");
+            });
+        }
+
+        let redo_tx = tx.clone();
+        if let Some(redo) = widget.menu_bar_actions.get("redo") {
+            redo.connect_activate(move |_, _| {
+                redo_tx.send(Message::Redo).expect("could not redo");
+            });
+        }
+
+        for (id, zoom) in &[
+            ("zoom_in", Zoom::In),
+            ("zoom_out", Zoom::Out),
+            ("zoom_reset", Zoom::Reset),
+        ] {
+            let zoom_tx = tx.clone();
+            let zoom = *zoom;
+            if let Some(action) = widget.menu_bar_actions.get(*id) {
+                action.connect_activate(move |_, _| {
+                    zoom_tx
+                        .send(Message::Zoom(zoom, None))
+                        .expect("could not zoom");
+                });
+            }
+        }
+
+        for difficulty in DIFFICULTIES.iter() {
+            bind_game_resize!(tx, widget, self.game, self.zooms, difficulty);
+        }
+
+        let seed_tx = tx.clone();
+        if let Some(new_game_from_seed) = widget.menu_bar_actions.get("new_game_from_seed") {
+            let game = self.game.clone();
+            new_game_from_seed.connect_activate(glib::clone!(@weak window => move |_, _| {
+                let seed = game.borrow().seed;
+
+                if let Some(seed) = seed_dialog(&window, seed) {
+                    seed_tx.send(Message::Seed(seed)).expect("could not reset");
+                }
+            }));
+        }
+
+        if let Some(first_click) = widget.menu_bar_actions.get("first_click") {
+            let game = self.game.clone();
+            first_click.connect_activate(move |action, parameter| {
+                let id = parameter.and_then(|parameter| parameter.get::<String>());
+
+                if let Some(first_click) = id.as_deref().and_then(FirstClick::from_id) {
+                    action.set_state(&first_click.id().to_variant());
+                    game.borrow_mut().first_click = first_click;
+                }
+            });
+        }
+
+        if let Some(endless) = widget.menu_bar_actions.get("endless") {
+            let game = self.game.clone();
+            let zooms = self.zooms.clone();
+            let endless_tx = tx.clone();
+            endless.connect_activate(glib::clone!(@weak widget => move |action, _| {
+                let endless = !action
+                    .get_state()
+                    .and_then(|state| state.get::<bool>())
+                    .unwrap_or(false);
+
+                if let Ok(mut game) = game.try_borrow_mut() {
+                    if endless {
+                        game.set_endless(true);
+                        show_board(&widget, &game, &zooms.borrow());
+                        endless_tx.send(Message::Reset).expect("could not reset");
+                    } else {
+                        let (width, height, mines) =
+                            (BEGINNER.width, BEGINNER.height, BEGINNER.mines);
+                        resize_board(
+                            &widget,
+                            &mut game,
+                            &zooms.borrow(),
+                            endless_tx.clone(),
+                            width,
+                            height,
+                            mines,
+                        );
+                    }
+                }
+            }));
+        }
+
+        if let Some(no_guess) = widget.menu_bar_actions.get("no_guess") {
+            let game = self.game.clone();
+            no_guess.connect_activate(move |action, _| {
+                let no_guess = !action
+                    .get_state()
+                    .and_then(|state| state.get::<bool>())
+                    .unwrap_or(false);
+
+                action.set_state(&no_guess.to_variant());
+                game.borrow_mut().no_guess = no_guess;
+            });
+        }
+
+        if let Some(question_marks) = widget.menu_bar_actions.get("question_marks") {
+            let game = self.game.clone();
+            question_marks.connect_activate(move |action, _| {
+                let question_marks = !action
+                    .get_state()
+                    .and_then(|state| state.get::<bool>())
+                    .unwrap_or(false);
+
+                action.set_state(&question_marks.to_variant());
+                game.borrow_mut().question_marks = question_marks;
+            });
+        }
+
+        if let Some(tenths) = widget.menu_bar_actions.get("tenths") {
+            let show_tenths = self.tenths.clone();
+            tenths.connect_activate(move |action, _| {
+                let tenths = !action
+                    .get_state()
+                    .and_then(|state| state.get::<bool>())
+                    .unwrap_or(false);
+
+                action.set_state(&tenths.to_variant());
+                show_tenths.set(tenths);
+            });
+        }
+
+        if let Some(preferences) = widget.menu_bar_actions.get("preferences") {
+            let bindings = self.bindings.clone();
+            preferences.connect_activate(glib::clone!(@weak window => move |_, _| {
+                let edited = match bindings_dialog(&window, &bindings.borrow()) {
+                    Some(edited) => edited,
+                    None => return,
+                };
+
+                if let Err(error) = edited.save() {
+                    error_dialog(&window, &format!("Could not save the bindings: {}", error));
+                }
+
+                if let Some(application) = window.get_application() {
+                    set_accels(&application, &edited);
+                }
+                bindings.replace(edited);
+            }));
+        }
+
+        if let Some(save_game) = widget.menu_bar_actions.get("save_game") {
+            let game = self.game.clone();
+            save_game.connect_activate(glib::clone!(@weak window => move |_, _| {
+                if game.borrow().endless.is_some() {
+                    error_dialog(&window, "Endless games can't be saved");
+                    return;
+                }
+
+                if let Some(path) = save_dialog(&window) {
+                    if let Err(error) = fs::write(&path, game.borrow().save()) {
+                        error_dialog(&window, &format!("Could not save the game: {}", error));
+                    }
+                }
+            }));
+        }
+
+        if let Some(open_game) = widget.menu_bar_actions.get("open_game") {
+            let game = self.game.clone();
+            let zooms = self.zooms.clone();
+            let tx = tx.clone();
+            open_game.connect_activate(glib::clone!(@weak widget => move |_, _| {
+                let path = match open_dialog(&widget.window) {
+                    Some(path) => path,
+                    None => return,
+                };
+
+                let loaded = fs::read_to_string(&path)
+                    .map_err(|error| error.to_string())
+                    .and_then(|text| game.borrow_mut().load(&text));
+
+                if let Err(error) = loaded {
+                    error_dialog(&widget.window, &format!("Could not open the game: {}", error));
+                    return;
+                }
+
+                show_board(&widget, &game.borrow(), &zooms.borrow());
+
+                tx.send(Message::Redraw).expect("could not redraw");
+            }));
+        }
+
+        if let Some(custom) = widget.menu_bar_actions.get("custom") {
+            let game = self.game.clone();
+            let zooms = self.zooms.clone();
+            custom.connect_activate(glib::clone!(@weak widget => move |_, _| {
+                let (width, height, mines) = {
+                    let game = game.borrow();
+                    (game.width, game.height, game.mine_count)
+                };
+
+                if let Some((width, height, mines)) =
+                    custom_dialog(&widget.window, width, height, mines)
+                {
+                    if let Ok(mut game) = game.try_borrow_mut() {
+                        resize_board(
+                            &widget,
+                            &mut game,
+                            &zooms.borrow(),
+                            tx.clone(),
+                            width,
+                            height,
+                            mines,
+                        );
+                    };
+                }
+            }));
         }
     }
 
     fn bind_clock(&self, tx: glib::Sender<Message>) {
         let game = self.game.clone();
+        let tenths = self.tenths.clone();
         let tx = tx.clone();
+        let mut shown = String::new();
 
         let tick = move || {
-            let game = game.borrow();
-
-            if game.active {
-                let elapsed = game.time.elapsed();
-
-                let secs = elapsed.as_secs();
-
-                let time = format!(
-                    "{minutes}:{seconds:0>2}",
-                    minutes = (secs / 60) % 60,
-                    seconds = secs % 60,
-                )
-                .to_string();
+            let time = format_time(game.borrow().elapsed(), tenths.get());
 
+            if time != shown {
+                shown = time.clone();
                 tx.send(Message::SetTime(time)).expect("could not set time");
             }
 
             glib::Continue(true)
         };
 
-        glib::timeout_add_local(400, tick);
+        glib::timeout_add_local(100, tick);
     }
 
-    fn update_main_ui_thread(&self, rx: glib::Receiver<Message>) {
-        let show_all_mines_widget = self.widget.clone();
-        let show_all_mines_game = self.game.clone();
-        let show_all_mines = move |completed: bool| {
-            let game = show_all_mines_game.borrow();
-            let widget = show_all_mines_widget.clone();
+    fn bind_board(&self, tx: glib::Sender<Message>) {
+        let board = &self.widget.board;
+        let bindings = &self.bindings;
+        let pressed = Rc::new(Cell::new(None));
+        let dragged = Rc::new(Cell::new(None));
+        let panning = Rc::new(Cell::new(false));
+        let chorded = Rc::new(Cell::new(false));
 
-            for (position, field) in &game.field {
-                if !field.is_mine && !field.is_flagged {
-                    continue;
+        board.area.connect_button_press_event(
+            glib::clone!(@strong board, @strong bindings, @strong pressed, @strong dragged => move |area, event| {
+                let (x, y) = event.get_position();
+                let button = event.get_button();
+                area.grab_focus();
+
+                if bindings.borrow().action_for_button(button, event.get_state()) == Some(Action::Reveal) {
+                    pressed.set(board.position_at(x, y));
+                    dragged.set(Some((button, event.get_root())));
                 }
 
-                if let Some(block) = widget.mines.borrow_mut().get(&position) {
-                    let button = &block.0;
-                    let ctx = button.get_style_context();
+                Inhibit(false)
+            }),
+        );
 
-                    button.set_can_focus(false);
-                    button.set_label(" ");
+        let send = tx.clone();
+        board.area.connect_button_release_event(
+            glib::clone!(@strong board, @strong bindings, @strong pressed, @strong dragged, @strong panning => move |_, event| {
+                let button = event.get_button();
 
-                    if !field.is_mine && field.is_flagged {
-                        ctx.add_class("btn_error");
-                    } else if field.is_mine && !field.is_flagged {
-                        ctx.add_class("btn_mine");
-                        if field.is_clicked {
-                            ctx.add_class("btn_mine_clicked");
-                        }
+                if dragged.get().map_or(false, |(dragging, _)| dragging == button) {
+                    dragged.set(None);
+
+                    if panning.replace(false) {
+                        return Inhibit(true);
                     }
                 }
-            }
 
-            widget
-                .button_reset
-                .get_style_context()
-                .add_class(if completed { "state_won" } else { "state_lost" });
+                let (x, y) = event.get_position();
+                let position = match board.position_at(x, y) {
+                    Some(position) => position,
+                    None => return Inhibit(false),
+                };
 
-            if completed {
-                widget.label_mines_left.set_label("0");
-            }
-        };
+                // Letting go of one button while another is still held chords, and the other
+                // button's release is then spent.
+                let held = [
+                    (1, ModifierType::BUTTON1_MASK),
+                    (2, ModifierType::BUTTON2_MASK),
+                    (3, ModifierType::BUTTON3_MASK),
+                ]
+                .iter()
+                .any(|(other, mask)| *other != button && event.get_state().contains(*mask));
 
-        let check_if_completed_widget = self.widget.clone();
-        let check_if_completed_game = self.game.clone();
-        let check_if_completed = move || -> bool {
-            let game = check_if_completed_game.clone();
-            let widget = check_if_completed_widget.clone();
-            let completed: bool;
-            {
-                let game = game.borrow();
-                let mines = game
-                    .field
-                    .iter()
-                    .filter_map(|(position, field)| {
-                        if game.mines.contains(&position) && !field.is_clicked {
-                            Some(position)
-                        } else {
-                            None
-                        }
-                    })
-                    .collect::<Vec<&Position>>();
-                let left = game
-                    .field
-                    .iter()
-                    .filter_map(|(position, field)| {
-                        if !field.is_clicked {
-                            Some(position)
-                        } else {
-                            None
-                        }
-                    })
-                    .collect::<Vec<&Position>>();
-                completed = mines.len() == game.mines.len() && mines.len() == left.len();
-            }
+                if held {
+                    chorded.set(true);
+                    pressed.set(None);
+                    send.send(Message::Chord(position)).expect("couldn't send");
+                    return Inhibit(true);
+                }
 
-            if completed {
-                let mut game = game.borrow_mut();
-                game.ended = true;
-                game.active = false;
-            }
+                if chorded.replace(false) {
+                    return Inhibit(true);
+                }
 
-            completed
-        };
+                let message = match bindings.borrow().action_for_button(button, event.get_state()) {
+                    Some(Action::Reveal) if pressed.take() == Some(position) => {
+                        Message::UpdateButton(position, false)
+                    }
+                    Some(Action::Reveal) | None => return Inhibit(false),
+                    Some(action) => match action_message(action, Some(position)) {
+                        Some(message) => message,
+                        None => return Inhibit(false),
+                    },
+                };
 
-        let widget = self.widget.clone();
-        let game = self.game.clone();
+                send.send(message).expect("couldn't send");
+                Inhibit(true)
+            }),
+        );
 
-        rx.attach(None, move |msg| {
-            match msg {
-                Message::Reset => {
-                    let ctx = widget.button_reset.get_style_context();
-                    clear_classes!(ctx, "state_");
-                    game.borrow_mut().new_mines();
-                    widget.label_time.set_label("0:00");
-                    widget
-                        .label_mines_left
-                        .set_label(&format!("{}", game.borrow().mines.len()));
-
-                    widget
-                        .mines
-                        .borrow_mut()
-                        .iter()
-                        .for_each(|(position, block)| {
-                            let button = &block.0;
-                            button.set_label(" ");
-                            button.set_can_focus(true);
-                            clear_classes!(button.get_style_context(), "btn_");
-                        });
+        board.area.connect_motion_notify_event(
+            glib::clone!(@strong board, @strong pressed => move |_, event| {
+                // The keyboard cursor follows the pointer, so keys act on the cell under it.
+                let (x, y) = event.get_position();
+                if let Some(position) = board.position_at(x, y) {
+                    board.set_cursor(Some(position));
                 }
-                Message::End => {
-                    let mut game = game.borrow_mut();
-                    game.ended = true;
-                    game.active = false;
-
-                    let ctx = widget.button_reset.get_style_context();
-                    clear_classes!(ctx, "state_");
-                    ctx.add_class("state_won");
-                    widget.label_mines_left.set_label(&"0");
-                    widget
-                        .mines
-                        .borrow_mut()
-                        .iter()
-                        .for_each(|(position, block)| {
-                            block.0.set_can_focus(false);
-                        });
+
+                // Dragging with the reveal button held pans the board instead of revealing.
+                let (root_x, root_y) = event.get_root();
+                if let Some((button, (from_x, from_y))) = dragged.get() {
+                    let (dx, dy) = (from_x - root_x, from_y - root_y);
+
+                    if panning.get() || dx.hypot(dy) > PAN_THRESHOLD {
+                        panning.set(true);
+                        pressed.set(None);
+                        board.pan(dx, dy);
+                        dragged.set(Some((button, (root_x, root_y))));
+                    }
                 }
-                Message::UpdateButton(position, block, flag) => {
-                    let button = block.0;
-                    let mut empty = false;
-                    let mut game_ended = false;
 
-                    'mut_closure: {
-                        let mut game = game.borrow_mut();
-                        let widget = widget.clone();
+                Inhibit(false)
+            }),
+        );
 
-                        if game.ended {
-                            break 'mut_closure;
-                        }
+        let send = tx.clone();
+        board.area.connect_scroll_event(move |_, event| {
+            if !event.get_state().contains(ModifierType::CONTROL_MASK) {
+                return Inhibit(false);
+            }
 
-                        if !game.active {
-                            game.start_timer();
-                        }
+            let zoom = match event.get_direction() {
+                ScrollDirection::Up => Zoom::In,
+                ScrollDirection::Down => Zoom::Out,
+                ScrollDirection::Smooth => match event.get_delta() {
+                    (_, dy) if dy < 0.0 => Zoom::In,
+                    (_, dy) if dy > 0.0 => Zoom::Out,
+                    _ => return Inhibit(true),
+                },
+                _ => return Inhibit(false),
+            };
 
-                        let field = game.field.get_mut(&position);
+            send.send(Message::Zoom(zoom, Some(event.get_position())))
+                .expect("couldn't send");
+            Inhibit(true)
+        });
+
+        let send = tx.clone();
+        board.area.connect_key_press_event(
+            glib::clone!(@strong board, @strong bindings => move |_, event| {
+                let (width, height) = board.size();
+                let keyval = event.get_keyval();
+                let modifiers = event.get_state() & gtk::accelerator_get_default_mod_mask();
+
+                if width == 0 || height == 0 {
+                    return Inhibit(false);
+                }
 
-                        if field.is_none() {
-                            break 'mut_closure;
+                if !modifiers.is_empty() || !is_cursor_key(*keyval) {
+                    let action = bindings.borrow().action_for_key(*keyval, event.get_state());
+
+                    return match action.and_then(|action| action_message(action, board.cursor())) {
+                        Some(message) => {
+                            send.send(message).expect("couldn't send");
+                            Inhibit(true)
                         }
+                        None => Inhibit(false),
+                    };
+                }
 
-                        let mut field = field.unwrap();
-                        let ctx = button.get_style_context();
+                let cursor = match board.cursor() {
+                    Some(cursor) => cursor,
+                    None => {
+                        let center = Position(width / 2, height / 2);
+                        board.set_cursor(Some(center));
+                        board.scroll_to(&center);
+                        return Inhibit(true);
+                    }
+                };
 
-                        if flag && field.is_clicked == false || field.is_flagged {
-                            field.is_flagged = !field.is_flagged;
-                            let mut mines: i16 =
-                                widget.label_mines_left.get_label().parse().unwrap_or(0);
+                let Position(x, y) = cursor;
+                let (last_x, last_y) = (width - 1, height - 1);
 
-                            if mines <= 0 && field.is_flagged {
-                                break 'mut_closure;
-                            }
+                let moved = match keyval {
+                    key::Left | key::h => Position(x.saturating_sub(1), y),
+                    key::Right | key::l => Position((x + 1).min(last_x), y),
+                    key::Up | key::k => Position(x, y.saturating_sub(1)),
+                    key::Down | key::j => Position(x, (y + 1).min(last_y)),
+                    key::Home => Position(0, y),
+                    key::End => Position(last_x, y),
+                    key::Page_Up => Position(x, 0),
+                    key::Page_Down => Position(x, last_y),
+                    _ => cursor,
+                };
 
-                            if field.is_flagged {
-                                mines -= 1;
-                                ctx.add_class("btn_flag");
-                            } else {
-                                mines += 1;
-                                ctx.remove_class("btn_flag");
-                            }
+                board.set_cursor(Some(moved));
+                board.scroll_to(&moved);
+                Inhibit(true)
+            }),
+        );
 
-                            button.set_label(" ");
+        board.area.grab_focus();
+    }
 
-                            widget.label_mines_left.set_label(&mines.to_string());
+    fn bind_minimap(&self) {
+        let widget = self.widget.clone();
+        let (board, minimap) = (&widget.board, &widget.minimap);
 
-                            break 'mut_closure;
-                        }
+        minimap.area.connect_draw(
+            glib::clone!(@strong board, @strong minimap, @strong self.game as game => move |_, cr| {
+                if let Ok(game) = game.try_borrow() {
+                    minimap.paint(cr, &game, board.visible());
+                }
 
-                        field.is_clicked = true;
-
-                        let (label, class_names) = if field.is_mine {
-                            (
-                                " ".to_string(),
-                                vec!["btn_mine", "btn_mine_clicked"]
-                                    .iter()
-                                    .map(|str| str.to_string())
-                                    .collect(),
-                            )
-                        } else if field.mines_around == 0 {
-                            (" ".to_string(), vec!["btn_empty".to_string()])
-                        } else {
-                            (
-                                field.mines_around.to_string(),
-                                vec!["btn_nearby".to_string(), field.mines_around_class_name()],
-                            )
-                        };
-
-                        if field.is_mine {
-                            game.active = false;
-                            game.ended = true;
-                        }
+                Inhibit(false)
+            }),
+        );
 
-                        button.set_label(&label);
-                        button.set_can_focus(false);
+        minimap.area.connect_button_press_event(
+            glib::clone!(@strong board, @strong minimap => move |_, event| {
+                let (x, y) = event.get_position();
+                let (x, y) = minimap.fraction_at(x, y);
+                board.jump_to(x, y);
 
-                        let ctx = button.get_style_context();
+                Inhibit(true)
+            }),
+        );
 
-                        clear_classes!(ctx, "btn_");
+        minimap.area.connect_motion_notify_event(
+            glib::clone!(@strong board, @strong minimap => move |_, event| {
+                let (x, y) = event.get_position();
+                let (x, y) = minimap.fraction_at(x, y);
+                board.jump_to(x, y);
 
-                        for class in &class_names {
-                            ctx.add_class(class);
+                Inhibit(true)
+            }),
+        );
+
+        // The view moves and resizes with scrolling, zooming and the window; the minimap follows.
+        if let Some((horizontal, vertical)) = board.adjustments() {
+            for adjustment in &[horizontal, vertical] {
+                adjustment.connect_value_changed(glib::clone!(@strong minimap => move |_| {
+                    minimap.area.queue_draw();
+                }));
+                adjustment.connect_changed(
+                    glib::clone!(@strong board, @strong minimap, @strong self.game as game => move |_| {
+                        if let Ok(game) = game.try_borrow() {
+                            let (_, _, width, height) = board.visible();
+                            minimap.update(game.width, game.height, width >= 1.0 && height >= 1.0);
                         }
+                    }),
+                );
+            }
+        }
+    }
 
-                        if game.ended {
-                            game_ended = true;
+    fn update_main_ui_thread(&self, rx: glib::Receiver<Message>, tx: glib::Sender<Message>) {
+        let widget = self.widget.clone();
+        let game = self.game.clone();
+        let zooms = self.zooms.clone();
+        let generation = Cell::new(0);
+        let cancel = RefCell::new(Arc::new(AtomicBool::new(false)));
+        let generating = Cell::new(false);
+
+        rx.attach(None, move |msg| {
+            let ticked = matches!(msg, Message::SetTime(_));
+
+            match msg {
+                Message::Reset | Message::Seed(_) => {
+                    next_generation(&generation, &cancel);
+                    generating.set(false);
+
+                    render(&widget, &[Event::Playing]);
+                    if let Message::Seed(seed) = msg {
+                        game.borrow_mut().new_mines_from_seed(seed);
+                    } else {
+                        game.borrow_mut().new_mines();
+                    }
+
+                    let game = game.borrow();
+                    match &game.endless {
+                        Some(endless) => {
+                            render(&widget, &game.board_events());
+
+                            if let Some(start) = endless.position(Coord(0, 0)) {
+                                widget.board.center_on(&start);
+                            }
+                        }
+                        None => {
+                            render(&widget, &[Event::MinesLeft(game.mines_left())]);
+                            widget.board.clear();
                         }
+                    }
+                }
+                Message::Pause => {
+                    let events = game.borrow_mut().toggle_pause();
 
-                        if class_names.contains(&"btn_empty".to_string()) {
-                            empty = true;
+                    render(&widget, &events);
+                }
+                Message::Zoom(zoom, anchor) => {
+                    let board = &widget.board;
+                    let level = match zoom {
+                        Zoom::In => Some(board.set_zoom(board.zoom() * ZOOM_STEP, anchor)),
+                        Zoom::Out => Some(board.set_zoom(board.zoom() / ZOOM_STEP, anchor)),
+                        Zoom::Reset => {
+                            board.set_zoom(default_zoom(&widget), None);
+                            None
                         }
+                    };
+
+                    let key = zoom_key(&game.borrow());
+                    if let Err(error) = zooms.borrow_mut().set(key, level) {
+                        eprintln!("could not remember the zoom: {}", error);
                     }
+                }
+                Message::UpdateButton(position, flag) if generating.get() => {}
+                Message::UpdateButton(position, false)
+                    if game.borrow().no_guess
+                        && game.borrow().endless.is_none()
+                        && !game.borrow().mines_placed
+                        && game.borrow().state.is_playable()
+                        && game
+                            .borrow()
+                            .field_at(&position)
+                            .map_or(false, |field| !field.is_clicked && !field.is_flagged) =>
+                {
+                    let (id, cancelled) = next_generation(&generation, &cancel);
+                    generating.set(true);
+
+                    let mut board = game.borrow().clone();
+                    let tx = tx.clone();
+
+                    thread::spawn(move || {
+                        let mut reported = Instant::now();
+                        board.place_mines(position, &cancelled, |attempt| {
+                            if reported.elapsed() >= PROGRESS_INTERVAL {
+                                reported = Instant::now();
+                                tx.send(Message::Generating(attempt))
+                                    .expect("could not send progress");
+                            }
+                        });
+
+                        if cancelled.load(Ordering::Relaxed) {
+                            return;
+                        }
 
-                    if empty {
-                        flood(widget.clone(), game.clone(), &position);
-                    } else if game_ended {
-                        show_all_mines(false);
-                    } else if check_if_completed() {
-                        show_all_mines(true);
+                        let failed = board.no_guess_failed;
+                        tx.send(Message::MinesPlaced(id, position, board.mines, failed))
+                            .expect("could not place mines");
+                    });
+                }
+                Message::Generating(attempt) => widget.label_seed.set_label(&format!(
+                    "Generating a no-guess board… (attempt {})",
+                    attempt
+                )),
+                Message::MinesPlaced(id, position, mines, failed) => {
+                    if id != generation.get() {
+                        return glib::Continue(true);
                     }
+
+                    generating.set(false);
+
+                    let events = {
+                        let mut game = game.borrow_mut();
+                        game.no_guess_failed = failed;
+                        game.reveal_with_mines(position, mines)
+                    };
+
+                    render(&widget, &events);
+                }
+                Message::UpdateButton(position, flag) => {
+                    let coord = game
+                        .borrow()
+                        .endless
+                        .as_ref()
+                        .map(|endless| endless.coord(&position));
+
+                    let events = {
+                        let mut game = game.borrow_mut();
+                        let (is_flagged, is_clicked) = game
+                            .field_at(&position)
+                            .map_or((false, false), |field| (field.is_flagged, field.is_clicked));
+
+                        if flag || is_flagged {
+                            game.toggle_flag(position)
+                        } else if is_clicked {
+                            game.chord(position)
+                        } else {
+                            game.reveal(position)
+                        }
+                    };
+
+                    render(&widget, &events);
+                    follow(&widget, &game.borrow(), coord);
+                }
+                Message::Question(position) => {
+                    let coord = game
+                        .borrow()
+                        .endless
+                        .as_ref()
+                        .map(|endless| endless.coord(&position));
+                    let events = game.borrow_mut().toggle_question(position);
+
+                    render(&widget, &events);
+                    follow(&widget, &game.borrow(), coord);
+                }
+                Message::Chord(position) => {
+                    let coord = game
+                        .borrow()
+                        .endless
+                        .as_ref()
+                        .map(|endless| endless.coord(&position));
+                    let events = game.borrow_mut().chord(position);
+
+                    render(&widget, &events);
+                    follow(&widget, &game.borrow(), coord);
+                }
+                Message::Redraw => {
+                    next_generation(&generation, &cancel);
+                    generating.set(false);
+
+                    let events = game.borrow().board_events();
+                    render(&widget, &events);
+                }
+                Message::Undo | Message::Redo if generating.get() => {}
+                Message::Undo | Message::Redo => {
+                    let events = match msg {
+                        Message::Undo => game.borrow_mut().undo(),
+                        _ => game.borrow_mut().redo(),
+                    };
+
+                    render(&widget, &events);
                 }
                 Message::SetTime(time) => widget.label_time.set_label(&time),
-                Message::SetMines(mines) => widget.label_mines_left.set_label(&mines),
                 _ => {}
             }
+
+            if !ticked && !generating.get() {
+                widget.label_seed.set_label(&seed_label(&game.borrow()));
+            }
+            if !ticked {
+                widget.minimap.area.queue_draw();
+            }
             widget.window.show_all();
             glib::Continue(true)
         });
@@ -350,10 +1019,11 @@ impl Application {
         let widget = self.widget.clone();
         let game = self.game.clone();
 
-        widget.label_time.set_label("0:00");
         widget
-            .label_mines_left
-            .set_label(&format!("{}", game.borrow().mines.len()));
+            .label_time
+            .set_label(&format_time(game.borrow().elapsed(), self.tenths.get()));
+        render(&widget, &[Event::MinesLeft(game.borrow().mines_left())]);
+        widget.label_seed.set_label(&seed_label(&game.borrow()));
 
         widget
             .button_reset
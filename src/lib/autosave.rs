@@ -0,0 +1,41 @@
+use crate::lib::{game::Game, state::State};
+use std::{
+    fs,
+    io::{self, ErrorKind},
+    path::PathBuf,
+};
+
+pub fn autosave_path() -> Option<PathBuf> {
+    glib::get_user_data_dir().map(|dir| dir.join("ms-roj").join("autosave.msroj"))
+}
+
+/// Keeps the game for the next launch if it is under way, and clears any older autosave if not.
+/// Endless games have no saved form, so they are never kept.
+pub fn write_autosave(game: &Game) -> io::Result<()> {
+    let path = match autosave_path() {
+        Some(path) => path,
+        None => return Ok(()),
+    };
+
+    if game.endless.is_some() || !matches!(game.state, State::Running | State::Paused) {
+        return match fs::remove_file(&path) {
+            Err(error) if error.kind() != ErrorKind::NotFound => Err(error),
+            _ => Ok(()),
+        };
+    }
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    fs::write(path, game.save())
+}
+
+/// Reads and removes the autosave, so a game is only ever offered back once.
+pub fn take_autosave() -> Option<String> {
+    let path = autosave_path()?;
+    let text = fs::read_to_string(&path).ok()?;
+    let _ = fs::remove_file(&path);
+
+    Some(text)
+}
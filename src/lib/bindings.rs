@@ -0,0 +1,464 @@
+//! Key and mouse bindings are kept in a plain text file, one action per line followed by the
+//! inputs bound to it:
+//!
+//! ```text
+//! reveal button1 space
+//! flag button3 f
+//! new_game <Primary>n
+//! ```
+//!
+//! Keys are written in GTK's accelerator syntax and mouse buttons as `button` and their number,
+//! with the same modifiers (`<Shift>button1`). Actions left out of the file keep their defaults.
+
+use crate::lib::difficulty::DIFFICULTIES;
+
+use gdk::{keys::constants as key, ModifierType};
+use std::{collections::HashMap, fmt, fs, io, path::PathBuf};
+
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub enum Action {
+    Reveal,
+    Flag,
+    Chord,
+    Question,
+    NewGame,
+    Pause,
+    Undo,
+    Redo,
+}
+
+pub const ACTIONS: [Action; 8] = [
+    Action::Reveal,
+    Action::Flag,
+    Action::Chord,
+    Action::Question,
+    Action::NewGame,
+    Action::Pause,
+    Action::Undo,
+    Action::Redo,
+];
+
+impl Action {
+    pub fn id(&self) -> &'static str {
+        match self {
+            Action::Reveal => "reveal",
+            Action::Flag => "flag",
+            Action::Chord => "chord",
+            Action::Question => "question",
+            Action::NewGame => "new_game",
+            Action::Pause => "pause",
+            Action::Undo => "undo",
+            Action::Redo => "redo",
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::Reveal => "Reveal",
+            Action::Flag => "Flag",
+            Action::Chord => "Chord",
+            Action::Question => "Question Mark",
+            Action::NewGame => "New Game",
+            Action::Pause => "Pause",
+            Action::Undo => "Undo",
+            Action::Redo => "Redo",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        ACTIONS.iter().find(|action| action.id() == id).copied()
+    }
+
+    /// The menu action this stands for, whose keys are set as its accelerators.
+    pub fn app_action(&self) -> Option<&'static str> {
+        match self {
+            Action::NewGame | Action::Pause | Action::Undo | Action::Redo => Some(self.id()),
+            Action::Reveal | Action::Flag | Action::Chord | Action::Question => None,
+        }
+    }
+
+    fn defaults(&self) -> &'static str {
+        match self {
+            Action::Reveal => "button1 space",
+            Action::Flag => "button3 f",
+            Action::Chord => "button2 d",
+            Action::Question => "q",
+            Action::NewGame => "<Primary>n",
+            Action::Pause => "p",
+            Action::Undo => "<Primary>z",
+            Action::Redo => "<Primary><Shift>z",
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub enum Input {
+    Key(u32, ModifierType),
+    Button(u32, ModifierType),
+}
+
+impl Input {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let split = text.rfind('>').map_or(0, |index| index + 1);
+        let (modifiers, name) = text.split_at(split);
+
+        if let Some(button) = name.strip_prefix("button") {
+            let button = button
+                .parse::<u32>()
+                .ok()
+                .filter(|button| *button != 0)
+                .ok_or_else(|| format!("Unknown mouse button {:?}", text))?;
+
+            // GTK only parses modifiers along with a key, so any key stands in for the button.
+            let (key, modifiers) = gtk::accelerator_parse(&format!("{}a", modifiers));
+            if key == 0 {
+                return Err(format!("Unknown modifiers in {:?}", text));
+            }
+
+            return Ok(Input::Button(button, modifiers));
+        }
+
+        match gtk::accelerator_parse(text) {
+            (0, _) => Err(format!("Unknown key {:?}", text)),
+            (key, modifiers) => Ok(Input::Key(key, modifiers)),
+        }
+    }
+
+    /// The input as written in the bindings file.
+    pub fn name(&self) -> String {
+        match *self {
+            Input::Key(key, modifiers) => gtk::accelerator_name(key, modifiers)
+                .map(|name| name.to_string())
+                .unwrap_or_default(),
+            Input::Button(button, modifiers) => {
+                let name = gtk::accelerator_name(*key::a, modifiers)
+                    .map(|name| name.to_string())
+                    .unwrap_or_default();
+
+                format!("{}button{}", name.trim_end_matches('a'), button)
+            }
+        }
+    }
+
+    /// The input as shown to the player.
+    pub fn label(&self) -> String {
+        match *self {
+            Input::Key(key, modifiers) => gtk::accelerator_get_label(key, modifiers)
+                .map(|label| label.to_string())
+                .unwrap_or_default(),
+            Input::Button(button, modifiers) => {
+                let label = gtk::accelerator_get_label(*key::a, modifiers)
+                    .map(|label| label.to_string())
+                    .unwrap_or_default();
+
+                format!("{}Mouse {}", label.trim_end_matches('A'), button)
+            }
+        }
+    }
+}
+
+/// Keys that move the board cursor, which can't be bound to anything else.
+pub fn is_cursor_key(keyval: u32) -> bool {
+    [
+        key::Left,
+        key::Right,
+        key::Up,
+        key::Down,
+        key::h,
+        key::j,
+        key::k,
+        key::l,
+        key::Home,
+        key::End,
+        key::Page_Up,
+        key::Page_Down,
+    ]
+    .iter()
+    .any(|key| **key == keyval)
+}
+
+/// Menu actions whose accelerators can't be rebound, as their id, name and accelerators.
+pub fn fixed_accels() -> Vec<(&'static str, &'static str, Vec<String>)> {
+    let mut accels = vec![
+        ("quit", "Quit", vec!["<Primary>q".to_string()]),
+        ("open_game", "Open Game", vec!["<Primary>o".to_string()]),
+        ("save_game", "Save Game", vec!["<Primary>s".to_string()]),
+        (
+            "zoom_in",
+            "Zoom In",
+            vec!["<Primary>plus".to_string(), "<Primary>equal".to_string()],
+        ),
+        ("zoom_out", "Zoom Out", vec!["<Primary>minus".to_string()]),
+        ("zoom_reset", "Reset Zoom", vec!["<Primary>0".to_string()]),
+    ];
+
+    for (index, difficulty) in DIFFICULTIES.iter().enumerate() {
+        accels.push((
+            difficulty.id,
+            difficulty.name,
+            vec![format!("<Primary>{}", index + 1)],
+        ));
+    }
+
+    accels
+}
+
+pub fn bindings_path() -> Option<PathBuf> {
+    glib::get_user_config_dir().map(|dir| dir.join("ms-roj").join("bindings"))
+}
+
+/// Parses the inputs of one action, separated by spaces.
+pub fn parse_inputs(text: &str) -> Result<Vec<Input>, String> {
+    text.split_whitespace().map(Input::parse).collect()
+}
+
+#[derive(Debug, Clone)]
+pub struct Bindings {
+    inputs: HashMap<Action, Vec<Input>>,
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        let inputs = ACTIONS
+            .iter()
+            .map(|action| {
+                let inputs = parse_inputs(action.defaults()).expect("default bindings parse");
+                (*action, inputs)
+            })
+            .collect();
+
+        Self { inputs }
+    }
+}
+
+impl Bindings {
+    /// Reads the bindings file, falling back on the defaults if there is none or it is broken.
+    pub fn load() -> Self {
+        let text = match bindings_path().and_then(|path| fs::read_to_string(path).ok()) {
+            Some(text) => text,
+            None => return Self::default(),
+        };
+
+        Self::parse(&text).unwrap_or_else(|error| {
+            eprintln!("could not read the bindings: {}", error);
+            Self::default()
+        })
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut bindings = Self::default();
+
+        for line in text.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let mut words = line.split_whitespace();
+            let id = words.next().unwrap_or_default();
+            let action = Action::from_id(id).ok_or_else(|| format!("Unknown action {:?}", id))?;
+            let inputs = words
+                .map(Input::parse)
+                .collect::<Result<Vec<Input>, String>>()?;
+
+            bindings.set(action, inputs);
+        }
+
+        match bindings.conflicts().first() {
+            Some(conflict) => Err(conflict.clone()),
+            None => Ok(bindings),
+        }
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let path = match bindings_path() {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        fs::write(path, self.to_string())
+    }
+
+    pub fn inputs(&self, action: Action) -> &[Input] {
+        self.inputs.get(&action).map_or(&[], |inputs| inputs)
+    }
+
+    pub fn set(&mut self, action: Action, inputs: Vec<Input>) {
+        self.inputs.insert(action, inputs);
+    }
+
+    /// Accelerators for the menu action, in the form GTK takes them. Only keys with a modifier
+    /// make it: accelerators are handled before the focused widget, so a plain letter would never
+    /// reach a text entry. The board handles plain keys itself.
+    pub fn accels(&self, action: Action) -> Vec<String> {
+        self.inputs(action)
+            .iter()
+            .filter(|input| matches!(input, Input::Key(_, modifiers) if !modifiers.is_empty()))
+            .map(Input::name)
+            .collect()
+    }
+
+    pub fn action_for_key(&self, keyval: u32, state: ModifierType) -> Option<Action> {
+        let input = Input::Key(
+            gdk::keyval_to_lower(keyval),
+            state & gtk::accelerator_get_default_mod_mask(),
+        );
+
+        self.action_for(&input)
+    }
+
+    pub fn action_for_button(&self, button: u32, state: ModifierType) -> Option<Action> {
+        let input = Input::Button(button, state & gtk::accelerator_get_default_mod_mask());
+
+        self.action_for(&input)
+    }
+
+    fn action_for(&self, input: &Input) -> Option<Action> {
+        ACTIONS
+            .iter()
+            .find(|action| self.inputs(**action).contains(input))
+            .copied()
+    }
+
+    /// Inputs bound to more than one action, to the cursor keys or to a fixed accelerator, as
+    /// messages for the player.
+    pub fn conflicts(&self) -> Vec<String> {
+        let mut conflicts = vec![];
+        let mut seen = HashMap::new();
+        let fixed = fixed_accels()
+            .into_iter()
+            .flat_map(|(_, name, accels)| {
+                accels
+                    .into_iter()
+                    .filter_map(move |accel| Some((Input::parse(&accel).ok()?, name)))
+            })
+            .collect::<HashMap<Input, &str>>();
+
+        for action in ACTIONS.iter() {
+            for input in self.inputs(*action) {
+                match input {
+                    Input::Key(key, modifiers) if modifiers.is_empty() && is_cursor_key(*key) => {
+                        conflicts.push(format!(
+                            "{} moves the cursor and can't be bound to {}",
+                            input.label(),
+                            action.name()
+                        ));
+                    }
+                    _ => {}
+                }
+
+                if let Some(name) = fixed.get(input) {
+                    conflicts.push(format!(
+                        "{} is taken by {} and can't be bound to {}",
+                        input.label(),
+                        name,
+                        action.name()
+                    ));
+                }
+
+                match seen.insert(*input, *action) {
+                    Some(other) if other != *action => conflicts.push(format!(
+                        "{} is bound to both {} and {}",
+                        input.label(),
+                        other.name(),
+                        action.name()
+                    )),
+                    _ => {}
+                }
+            }
+        }
+
+        conflicts
+    }
+}
+
+impl fmt::Display for Bindings {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for action in ACTIONS.iter() {
+            write!(f, "{}", action.id())?;
+
+            for input in self.inputs(*action) {
+                write!(f, " {}", input.name())?;
+            }
+
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn with(action: Action, inputs: &str) -> Bindings {
+        let mut bindings = Bindings::default();
+        bindings.set(action, parse_inputs(inputs).expect("the inputs parse"));
+        bindings
+    }
+
+    /// GTK has to be initialized, and only ever from one thread, before it parses accelerators,
+    /// so everything that needs it is checked here.
+    #[test]
+    #[ignore = "needs a display to initialize GTK"]
+    fn parses_bindings_and_detects_conflicts() {
+        gtk::init().expect("GTK initializes");
+
+        // inputs
+        assert_eq!(
+            Input::parse("<Shift>button3"),
+            Ok(Input::Button(3, ModifierType::SHIFT_MASK))
+        );
+        assert_eq!(
+            Input::parse("<Primary>n"),
+            Ok(Input::Key(*key::n, ModifierType::CONTROL_MASK))
+        );
+        assert_eq!(
+            Input::parse("<Shift>button3").map(|input| input.name()),
+            Ok("<Shift>button3".to_string())
+        );
+        assert!(Input::parse("button0").is_err());
+        assert!(Input::parse("nokey").is_err());
+
+        // the file, where actions left out keep their defaults
+        let bindings = Bindings::parse("# comment\n\nflag\tbutton3 <Shift>button1\n")
+            .expect("the bindings parse");
+        assert_eq!(
+            bindings.inputs(Action::Flag),
+            &[
+                Input::Button(3, ModifierType::empty()),
+                Input::Button(1, ModifierType::SHIFT_MASK)
+            ]
+        );
+        assert_eq!(
+            bindings.inputs(Action::Reveal),
+            Bindings::default().inputs(Action::Reveal)
+        );
+
+        let again = Bindings::parse(&bindings.to_string()).expect("the bindings parse");
+        for action in ACTIONS.iter() {
+            assert_eq!(again.inputs(*action), bindings.inputs(*action));
+        }
+
+        assert!(Bindings::parse("explode button1").is_err());
+        assert!(Bindings::parse("question space").is_err());
+
+        // conflicts with other actions, the cursor keys and the fixed accelerators
+        assert!(Bindings::default().conflicts().is_empty());
+        assert_eq!(with(Action::Question, "space").conflicts().len(), 1);
+        assert_eq!(with(Action::Question, "h").conflicts().len(), 1);
+        assert_eq!(with(Action::Question, "<Primary>q").conflicts().len(), 1);
+        assert_eq!(with(Action::Question, "<Primary>1").conflicts().len(), 1);
+        assert!(with(Action::Question, "<Shift>h").conflicts().is_empty());
+
+        // only keys with a modifier become accelerators
+        assert_eq!(
+            with(Action::Pause, "p <Primary>p button2").accels(Action::Pause),
+            vec!["<Primary>p".to_string()]
+        );
+    }
+}
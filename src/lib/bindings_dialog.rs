@@ -0,0 +1,105 @@
+use crate::lib::bindings::{parse_inputs, Bindings, Input, ACTIONS};
+
+use gtk::{
+    prelude::*, ApplicationWindow, Button, Dialog, DialogFlags, Entry, Grid, Label, ResponseType,
+};
+
+fn inputs_text(inputs: &[Input]) -> String {
+    inputs
+        .iter()
+        .map(Input::name)
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+pub fn bindings_dialog(window: &ApplicationWindow, bindings: &Bindings) -> Option<Bindings> {
+    let dialog = Dialog::with_buttons(
+        Some("Preferences"),
+        Some(window),
+        DialogFlags::MODAL | DialogFlags::DESTROY_WITH_PARENT,
+        &[("Cancel", ResponseType::Cancel), ("Save", ResponseType::Ok)],
+    );
+    dialog.set_default_response(ResponseType::Ok);
+
+    let grid = Grid::new();
+    grid.set_row_spacing(8);
+    grid.set_column_spacing(8);
+    grid.set_border_width(8);
+
+    let hint = Label::new(Some(
+        "Inputs are separated by spaces: keys like <Primary>n or space, mouse buttons like button1 or <Shift>button1.",
+    ));
+    hint.set_line_wrap(true);
+    hint.set_max_width_chars(40);
+    grid.attach(&hint, 0, 0, 2, 1);
+
+    let entries = ACTIONS
+        .iter()
+        .enumerate()
+        .map(|(row, action)| {
+            let entry = Entry::new();
+            entry.set_text(&inputs_text(bindings.inputs(*action)));
+            entry.set_activates_default(true);
+
+            let label = Label::new(Some(action.name()));
+            label.set_halign(gtk::Align::Start);
+
+            grid.attach(&label, 0, row as i32 + 1, 1, 1);
+            grid.attach(&entry, 1, row as i32 + 1, 1, 1);
+
+            entry
+        })
+        .collect::<Vec<Entry>>();
+
+    let defaults = Button::with_label("Restore Defaults");
+    defaults.connect_clicked(glib::clone!(@strong entries => move |_| {
+        let bindings = Bindings::default();
+
+        for (action, entry) in ACTIONS.iter().zip(entries.iter()) {
+            entry.set_text(&inputs_text(bindings.inputs(*action)));
+        }
+    }));
+
+    let label_error = Label::new(None);
+    label_error.set_line_wrap(true);
+    label_error.set_max_width_chars(40);
+    label_error.get_style_context().add_class("error");
+
+    let rows = ACTIONS.len() as i32 + 1;
+    grid.attach(&defaults, 0, rows, 2, 1);
+    grid.attach(&label_error, 0, rows + 1, 2, 1);
+
+    dialog.get_content_area().add(&grid);
+    dialog.show_all();
+
+    let edited = loop {
+        if dialog.run() != ResponseType::Ok {
+            break None;
+        }
+
+        let mut edited = bindings.clone();
+        let parsed = ACTIONS
+            .iter()
+            .zip(entries.iter())
+            .try_for_each(|(action, entry)| {
+                let inputs = parse_inputs(&entry.get_text())
+                    .map_err(|error| format!("{}: {}", action.name(), error))?;
+
+                edited.set(*action, inputs);
+                Ok(())
+            })
+            .and_then(|()| match edited.conflicts().as_slice() {
+                [] => Ok(()),
+                conflicts => Err(conflicts.join("\n")),
+            });
+
+        match parsed {
+            Ok(()) => break Some(edited),
+            Err(error) => label_error.set_label(&error),
+        }
+    };
+
+    unsafe { dialog.destroy() }
+
+    edited
+}
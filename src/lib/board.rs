@@ -0,0 +1,485 @@
+use crate::lib::position::Position;
+
+use gdk::EventMask;
+use gtk::{prelude::*, Adjustment, DrawingArea, PolicyType, ScrolledWindow, StyleContext};
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+};
+
+/// Cell size at 100% zoom; the board is always painted at this size and scaled.
+pub const CELL_SIZE: i32 = 40;
+pub const MIN_ZOOM: f64 = 0.25;
+pub const MAX_ZOOM: f64 = 4.0;
+pub const ZOOM_STEP: f64 = 1.2;
+/// Cells kept in view around a cell scrolled to.
+const SCROLL_MARGIN: f64 = 3.0;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Zoom {
+    In,
+    Out,
+    Reset,
+}
+
+/// How a cell is drawn. Each look stands for the CSS classes a cell is painted with, so the
+/// board keeps following the `.mine` rules in `style.css`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Look {
+    Hidden,
+    Flagged,
+    Questioned,
+    Revealed(u16),
+    Exploded,
+    Mine,
+    WrongFlag,
+}
+
+impl Look {
+    fn classes(self) -> &'static [&'static str] {
+        match self {
+            Look::Hidden => &[],
+            Look::Flagged => &["btn_flag"],
+            Look::Questioned => &["btn_question"],
+            Look::Revealed(0) => &["btn_empty"],
+            Look::Revealed(1) => &["btn_nearby", "btn_nearby_one"],
+            Look::Revealed(2) => &["btn_nearby", "btn_nearby_two"],
+            Look::Revealed(_) => &["btn_nearby", "btn_nearby_multiple"],
+            Look::Exploded => &["btn_mine", "btn_mine_clicked"],
+            Look::Mine => &["btn_mine"],
+            Look::WrongFlag => &["btn_flag", "btn_error"],
+        }
+    }
+}
+
+/// The minefield as a single drawing area, painting every cell itself instead of holding a
+/// button per cell. The area sits in a scrolled view so boards bigger than the screen can be
+/// panned.
+#[derive(Clone, Debug)]
+pub struct Board {
+    pub area: DrawingArea,
+    pub view: ScrolledWindow,
+    size: Rc<Cell<(u16, u16)>>,
+    zoom: Rc<Cell<f64>>,
+    looks: Rc<RefCell<Vec<Look>>>,
+    covered: Rc<Cell<bool>>,
+    cursor: Rc<Cell<Option<Position>>>,
+}
+
+impl Board {
+    pub fn new() -> Self {
+        let area = DrawingArea::new();
+        area.set_can_focus(true);
+        area.add_events(
+            EventMask::BUTTON_PRESS_MASK
+                | EventMask::BUTTON_RELEASE_MASK
+                | EventMask::POINTER_MOTION_MASK
+                | EventMask::LEAVE_NOTIFY_MASK
+                | EventMask::SCROLL_MASK
+                | EventMask::SMOOTH_SCROLL_MASK
+                | EventMask::KEY_PRESS_MASK,
+        );
+
+        let view = ScrolledWindow::new(None::<&Adjustment>, None::<&Adjustment>);
+        view.set_policy(PolicyType::Automatic, PolicyType::Automatic);
+        view.set_propagate_natural_width(true);
+        view.set_propagate_natural_height(true);
+
+        let board = Self {
+            area,
+            view,
+            size: Rc::new(Cell::new((0, 0))),
+            zoom: Rc::new(Cell::new(1.0)),
+            looks: Rc::new(RefCell::new(vec![])),
+            covered: Rc::new(Cell::new(false)),
+            cursor: Rc::new(Cell::new(None)),
+        };
+
+        let (size, zoom, looks, covered, cursor) = (
+            board.size.clone(),
+            board.zoom.clone(),
+            board.looks.clone(),
+            board.covered.clone(),
+            board.cursor.clone(),
+        );
+        board.area.connect_draw(move |area, cr| {
+            if covered.get() {
+                return Inhibit(false);
+            }
+
+            cr.scale(zoom.get(), zoom.get());
+
+            let (width, height) = size.get();
+            let looks = looks.borrow();
+            let cell = CELL_SIZE as f64;
+            let (x1, y1, x2, y2) = cr.clip_extents();
+
+            let columns = (x1 / cell).max(0.0) as u16..((x2 / cell).ceil() as u16).min(width);
+            let rows = (y1 / cell).max(0.0) as u16..((y2 / cell).ceil() as u16).min(height);
+
+            let mut cells = rows
+                .flat_map(|y| columns.clone().map(move |x| (x, y)))
+                .map(|(x, y)| (x, y, looks[y as usize * width as usize + x as usize]))
+                .collect::<Vec<(u16, u16, Look)>>();
+
+            // Cells that look the same share one style, so each look is set up once per frame.
+            let ctx = area.get_style_context();
+            while let Some(&(_, _, look)) = cells.first() {
+                ctx.save();
+                ctx.add_class("mine");
+                for class in look.classes() {
+                    ctx.add_class(class);
+                }
+
+                let layout = match look {
+                    Look::Revealed(mines_around) if mines_around != 0 => {
+                        Some(area.create_pango_layout(Some(&mines_around.to_string())))
+                    }
+                    _ => None,
+                };
+
+                cells.retain(|&(x, y, other)| {
+                    if other != look {
+                        return true;
+                    }
+
+                    let (x, y) = (x as f64 * cell, y as f64 * cell);
+                    paint_cell(&ctx, cr, x, y, cell, layout.as_ref());
+                    false
+                });
+
+                ctx.restore();
+            }
+
+            if let (Some(cursor), true) = (cursor.get(), area.has_focus()) {
+                ctx.save();
+                ctx.add_class("mine");
+                ctx.add_class("cursor");
+                gtk::render_focus(
+                    &ctx,
+                    cr,
+                    cursor.0 as f64 * cell,
+                    cursor.1 as f64 * cell,
+                    cell,
+                    cell,
+                );
+                ctx.restore();
+            }
+
+            Inhibit(false)
+        });
+
+        // The cursor is only drawn while the board has the keyboard.
+        board.area.connect_focus_in_event(|area, _| {
+            area.queue_draw();
+            Inhibit(false)
+        });
+        board.area.connect_focus_out_event(|area, _| {
+            area.queue_draw();
+            Inhibit(false)
+        });
+
+        board
+    }
+
+    pub fn resize(&self, width: u16, height: u16) {
+        self.size.set((width, height));
+        self.looks
+            .replace(vec![Look::Hidden; width as usize * height as usize]);
+        self.cursor.set(self.cursor.get().map(|cursor| {
+            Position(
+                cursor.0.min(width.saturating_sub(1)),
+                cursor.1.min(height.saturating_sub(1)),
+            )
+        }));
+        self.update_size();
+    }
+
+    pub fn size(&self) -> (u16, u16) {
+        self.size.get()
+    }
+
+    pub fn cursor(&self) -> Option<Position> {
+        self.cursor.get()
+    }
+
+    pub fn set_cursor(&self, cursor: Option<Position>) {
+        let previous = self.cursor.replace(cursor);
+
+        if previous == cursor {
+            return;
+        }
+
+        for position in previous.iter().chain(cursor.iter()) {
+            self.queue_cell(position);
+        }
+    }
+
+    pub fn zoom(&self) -> f64 {
+        self.zoom.get()
+    }
+
+    /// Scales the board, keeping the point at `anchor` (in board coordinates) under the pointer.
+    /// Returns the zoom actually used once clamped.
+    pub fn set_zoom(&self, zoom: f64, anchor: Option<(f64, f64)>) -> f64 {
+        let zoom = zoom.max(MIN_ZOOM).min(MAX_ZOOM);
+        let ratio = zoom / self.zoom.get();
+
+        self.zoom.set(zoom);
+        self.update_size();
+
+        if let (Some((x, y)), Some((horizontal, vertical))) = (anchor, self.adjustments()) {
+            let (left, top) = (
+                x * ratio - (x - horizontal.get_value()),
+                y * ratio - (y - vertical.get_value()),
+            );
+
+            // The new scroll range only exists once the view has been laid out again.
+            glib::idle_add_local(move || {
+                horizontal.set_value(left);
+                vertical.set_value(top);
+                glib::Continue(false)
+            });
+        }
+
+        zoom
+    }
+
+    /// The largest zoom that shows the whole board within `width` × `height` pixels.
+    pub fn fit_zoom(&self, width: i32, height: i32) -> f64 {
+        let (columns, rows) = self.size.get();
+        let (board_width, board_height) = (
+            (columns.max(1) as i32 * CELL_SIZE) as f64,
+            (rows.max(1) as i32 * CELL_SIZE) as f64,
+        );
+
+        (width as f64 / board_width)
+            .min(height as f64 / board_height)
+            .max(MIN_ZOOM)
+            .min(MAX_ZOOM)
+    }
+
+    /// Caps how large the view grows before it starts scrolling.
+    pub fn set_max_size(&self, width: i32, height: i32) {
+        self.view.set_max_content_width(width);
+        self.view.set_max_content_height(height);
+    }
+
+    /// Scrolls the view by the given number of pixels.
+    pub fn pan(&self, dx: f64, dy: f64) {
+        let (horizontal, vertical) = match self.adjustments() {
+            Some(adjustments) => adjustments,
+            None => return,
+        };
+
+        horizontal.set_value(horizontal.get_value() + dx);
+        vertical.set_value(vertical.get_value() + dy);
+    }
+
+    pub fn clear(&self) {
+        for look in self.looks.borrow_mut().iter_mut() {
+            *look = Look::Hidden;
+        }
+        self.area.queue_draw();
+    }
+
+    pub fn get(&self, position: &Position) -> Option<Look> {
+        let index = self.index(position)?;
+
+        Some(self.looks.borrow()[index])
+    }
+
+    /// Changes the look of one cell, redrawing only that cell.
+    pub fn set(&self, position: &Position, look: Look) {
+        let index = match self.index(position) {
+            Some(index) => index,
+            None => return,
+        };
+
+        if self.looks.borrow()[index] == look {
+            return;
+        }
+
+        self.looks.borrow_mut()[index] = look;
+        self.queue_cell(position);
+    }
+
+    fn queue_cell(&self, position: &Position) {
+        let (x, y, cell) = (
+            position.0 as f64 * self.cell_size(),
+            position.1 as f64 * self.cell_size(),
+            self.cell_size(),
+        );
+        self.area.queue_draw_area(
+            x.floor() as i32,
+            y.floor() as i32,
+            (x + cell).ceil() as i32 - x.floor() as i32,
+            (y + cell).ceil() as i32 - y.floor() as i32,
+        );
+    }
+
+    /// Paints the board empty, leaving its size as it is.
+    pub fn set_covered(&self, covered: bool) {
+        self.covered.set(covered);
+        self.area.queue_draw();
+    }
+
+    pub fn position_at(&self, x: f64, y: f64) -> Option<Position> {
+        let (width, height) = self.size.get();
+
+        if x < 0.0 || y < 0.0 {
+            return None;
+        }
+
+        let position = Position((x / self.cell_size()) as u16, (y / self.cell_size()) as u16);
+
+        if position.0 < width && position.1 < height {
+            Some(position)
+        } else {
+            None
+        }
+    }
+
+    /// Scrolls along with cells that moved right and down by this many cells, so the same part
+    /// of the board stays in view.
+    pub fn shift(&self, x: u16, y: u16) {
+        if let Some(cursor) = self.cursor.get() {
+            self.set_cursor(Some(Position(cursor.0 + x, cursor.1 + y)));
+        }
+
+        let (horizontal, vertical) = match self.adjustments() {
+            Some(adjustments) => adjustments,
+            None => return,
+        };
+        let (dx, dy) = (x as f64 * self.cell_size(), y as f64 * self.cell_size());
+
+        glib::idle_add_local(move || {
+            horizontal.set_value(horizontal.get_value() + dx);
+            vertical.set_value(vertical.get_value() + dy);
+            glib::Continue(false)
+        });
+    }
+
+    /// Scrolls just enough to show the cell with a few cells around it.
+    pub fn scroll_to(&self, position: &Position) {
+        let (horizontal, vertical) = match self.adjustments() {
+            Some(adjustments) => adjustments,
+            None => return,
+        };
+        let (x, y, margin) = (
+            position.0 as f64 * self.cell_size(),
+            position.1 as f64 * self.cell_size(),
+            SCROLL_MARGIN * self.cell_size(),
+        );
+        let cell = self.cell_size();
+
+        glib::idle_add_local(move || {
+            horizontal.clamp_page(x - margin, x + cell + margin);
+            vertical.clamp_page(y - margin, y + cell + margin);
+            glib::Continue(false)
+        });
+    }
+
+    pub fn center_on(&self, position: &Position) {
+        let (horizontal, vertical) = match self.adjustments() {
+            Some(adjustments) => adjustments,
+            None => return,
+        };
+        let (x, y) = (
+            (position.0 as f64 + 0.5) * self.cell_size(),
+            (position.1 as f64 + 0.5) * self.cell_size(),
+        );
+
+        glib::idle_add_local(move || {
+            horizontal.set_value(x - horizontal.get_page_size() / 2.0);
+            vertical.set_value(y - vertical.get_page_size() / 2.0);
+            glib::Continue(false)
+        });
+    }
+
+    /// The part of the board in view, as fractions of it: left, top, width and height.
+    pub fn visible(&self) -> (f64, f64, f64, f64) {
+        let (horizontal, vertical) = match self.adjustments() {
+            Some(adjustments) => adjustments,
+            None => return (0.0, 0.0, 1.0, 1.0),
+        };
+        let fraction = |adjustment: &Adjustment| {
+            let range = (adjustment.get_upper() - adjustment.get_lower()).max(1.0);
+
+            (
+                (adjustment.get_value() - adjustment.get_lower()) / range,
+                (adjustment.get_page_size() / range).min(1.0),
+            )
+        };
+
+        let ((left, width), (top, height)) = (fraction(&horizontal), fraction(&vertical));
+
+        (left, top, width, height)
+    }
+
+    /// Centres the view on a point given as fractions of the board.
+    pub fn jump_to(&self, x: f64, y: f64) {
+        let (horizontal, vertical) = match self.adjustments() {
+            Some(adjustments) => adjustments,
+            None => return,
+        };
+
+        for (adjustment, fraction) in &[(horizontal, x), (vertical, y)] {
+            let range = adjustment.get_upper() - adjustment.get_lower();
+
+            adjustment.set_value(
+                adjustment.get_lower() + fraction * range - adjustment.get_page_size() / 2.0,
+            );
+        }
+    }
+
+    pub fn adjustments(&self) -> Option<(Adjustment, Adjustment)> {
+        Some((self.view.get_hadjustment()?, self.view.get_vadjustment()?))
+    }
+
+    fn cell_size(&self) -> f64 {
+        CELL_SIZE as f64 * self.zoom.get()
+    }
+
+    fn update_size(&self) {
+        let (width, height) = self.size.get();
+
+        self.area.set_size_request(
+            (width as f64 * self.cell_size()).ceil() as i32,
+            (height as f64 * self.cell_size()).ceil() as i32,
+        );
+        self.area.queue_draw();
+    }
+
+    fn index(&self, position: &Position) -> Option<usize> {
+        let (width, height) = self.size.get();
+
+        if position.0 < width && position.1 < height {
+            Some(position.1 as usize * width as usize + position.0 as usize)
+        } else {
+            None
+        }
+    }
+}
+
+fn paint_cell(
+    ctx: &StyleContext,
+    cr: &cairo::Context,
+    x: f64,
+    y: f64,
+    size: f64,
+    layout: Option<&pango::Layout>,
+) {
+    gtk::render_background(ctx, cr, x, y, size, size);
+    gtk::render_frame(ctx, cr, x, y, size, size);
+
+    if let Some(layout) = layout {
+        let (width, height) = layout.get_pixel_size();
+        let (left, top) = (
+            x + (size - width as f64) / 2.0,
+            y + (size - height as f64) / 2.0,
+        );
+
+        gtk::render_layout(ctx, cr, left, top, layout);
+    }
+}
@@ -0,0 +1,40 @@
+use std::time::{Duration, Instant};
+
+/// A stopwatch that adds up the time spent running, so pauses don't count towards it.
+#[derive(Debug, Clone, Default)]
+pub struct Clock {
+    played: Duration,
+    running_since: Option<Instant>,
+}
+
+impl Clock {
+    pub fn new(played: Duration) -> Self {
+        Self {
+            played,
+            running_since: None,
+        }
+    }
+
+    pub fn start(&mut self) {
+        if self.running_since.is_none() {
+            self.running_since = Some(Instant::now());
+        }
+    }
+
+    pub fn stop(&mut self) {
+        if let Some(since) = self.running_since.take() {
+            self.played += since.elapsed();
+        }
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.played
+            + self
+                .running_since
+                .map_or(Duration::from_secs(0), |since| since.elapsed())
+    }
+}
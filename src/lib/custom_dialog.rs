@@ -0,0 +1,91 @@
+use crate::lib::game::check_board;
+
+use gtk::{
+    prelude::*, ApplicationWindow, Dialog, DialogFlags, Grid, Label, RadioButton, ResponseType,
+    SpinButton,
+};
+
+pub fn custom_dialog(
+    window: &ApplicationWindow,
+    width: u16,
+    height: u16,
+    mines: u16,
+) -> Option<(u16, u16, u16)> {
+    let dialog = Dialog::with_buttons(
+        Some("Custom"),
+        Some(window),
+        DialogFlags::MODAL | DialogFlags::DESTROY_WITH_PARENT,
+        &[
+            ("Cancel", ResponseType::Cancel),
+            ("New Game", ResponseType::Ok),
+        ],
+    );
+    dialog.set_default_response(ResponseType::Ok);
+
+    let grid = Grid::new();
+    grid.set_row_spacing(8);
+    grid.set_column_spacing(8);
+    grid.set_border_width(8);
+
+    let spin_width = SpinButton::with_range(2.0, 100.0, 1.0);
+    spin_width.set_value(width as f64);
+
+    let spin_height = SpinButton::with_range(2.0, 100.0, 1.0);
+    spin_height.set_value(height as f64);
+
+    let radio_mines = RadioButton::with_label("Mines");
+    let spin_mines = SpinButton::with_range(1.0, 9999.0, 1.0);
+    spin_mines.set_value(mines as f64);
+
+    let radio_density = RadioButton::with_label_from_widget(&radio_mines, "Density (%)");
+    let spin_density = SpinButton::with_range(1.0, 99.0, 1.0);
+    spin_density.set_value((mines as u32 * 100 / (width as u32 * height as u32)) as f64);
+    spin_density.set_sensitive(false);
+
+    radio_mines.connect_toggled(
+        glib::clone!(@weak spin_mines, @weak spin_density => move |radio| {
+            spin_mines.set_sensitive(radio.get_active());
+            spin_density.set_sensitive(!radio.get_active());
+        }),
+    );
+
+    let label_error = Label::new(None);
+    label_error.get_style_context().add_class("error");
+
+    grid.attach(&Label::new(Some("Width")), 0, 0, 1, 1);
+    grid.attach(&spin_width, 1, 0, 1, 1);
+    grid.attach(&Label::new(Some("Height")), 0, 1, 1, 1);
+    grid.attach(&spin_height, 1, 1, 1, 1);
+    grid.attach(&radio_mines, 0, 2, 1, 1);
+    grid.attach(&spin_mines, 1, 2, 1, 1);
+    grid.attach(&radio_density, 0, 3, 1, 1);
+    grid.attach(&spin_density, 1, 3, 1, 1);
+    grid.attach(&label_error, 0, 4, 2, 1);
+
+    dialog.get_content_area().add(&grid);
+    dialog.show_all();
+
+    let board = loop {
+        if dialog.run() != ResponseType::Ok {
+            break None;
+        }
+
+        let width = spin_width.get_value_as_int() as u16;
+        let height = spin_height.get_value_as_int() as u16;
+        let mines = if radio_mines.get_active() {
+            spin_mines.get_value_as_int() as u16
+        } else {
+            let cells = width as u32 * height as u32;
+            (cells * spin_density.get_value_as_int() as u32 / 100) as u16
+        };
+
+        match check_board(width, height, mines) {
+            Ok(()) => break Some((width, height, mines)),
+            Err(error) => label_error.set_label(&error),
+        }
+    };
+
+    unsafe { dialog.destroy() }
+
+    board
+}
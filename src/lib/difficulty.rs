@@ -0,0 +1,42 @@
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Difficulty {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub width: u16,
+    pub height: u16,
+    pub mines: u16,
+}
+
+pub const DIFFICULTIES: [Difficulty; 3] = [
+    Difficulty {
+        id: "beginner",
+        name: "Beginner",
+        width: 9,
+        height: 9,
+        mines: 10,
+    },
+    Difficulty {
+        id: "intermediate",
+        name: "Intermediate",
+        width: 16,
+        height: 16,
+        mines: 40,
+    },
+    Difficulty {
+        id: "expert",
+        name: "Expert",
+        width: 30,
+        height: 16,
+        mines: 99,
+    },
+];
+
+pub const BEGINNER: &Difficulty = &DIFFICULTIES[0];
+
+impl Difficulty {
+    pub fn find(width: u16, height: u16, mines: u16) -> Option<&'static Difficulty> {
+        DIFFICULTIES.iter().find(|difficulty| {
+            difficulty.width == width && difficulty.height == height && difficulty.mines == mines
+        })
+    }
+}
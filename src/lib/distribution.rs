@@ -0,0 +1,84 @@
+use crate::lib::{difficulty::Difficulty, gen_mines};
+
+pub struct Distribution {
+    pub boards: u32,
+    pub counts: Vec<u32>,
+    pub expected: f64,
+    pub chi_squared: f64,
+    pub degrees_of_freedom: u32,
+}
+
+impl Distribution {
+    /// Standard score of the chi-squared statistic, close to 0 when mines are placed uniformly.
+    pub fn z_score(&self) -> f64 {
+        let k = self.degrees_of_freedom as f64;
+
+        (self.chi_squared - k) / (2.0 * k).sqrt()
+    }
+}
+
+pub fn mine_distribution(difficulty: &Difficulty, boards: u32) -> Distribution {
+    let (width, height) = (difficulty.width, difficulty.height);
+    let cells = width as usize * height as usize;
+    let mut counts = vec![0; cells];
+
+    for seed in 0..boards {
+        for mine in gen_mines(width, height, difficulty.mines, seed as u64, &[]) {
+            counts[mine.1 as usize * width as usize + mine.0 as usize] += 1;
+        }
+    }
+
+    // every board holds exactly `mines` mines, so each cell count is binomial with variance
+    // `expected * (1 - p)` rather than the `expected` a plain chi-squared test assumes
+    let p = difficulty.mines as f64 / cells as f64;
+    let expected = boards as f64 * p;
+    let chi_squared = counts
+        .iter()
+        .map(|count| (*count as f64 - expected).powi(2) / (expected * (1.0 - p)))
+        .sum();
+
+    Distribution {
+        boards,
+        counts,
+        expected,
+        chi_squared,
+        degrees_of_freedom: cells as u32 - 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lib::difficulty::DIFFICULTIES;
+
+    /// Slow, so it only runs when asked for, as `make distribution` does.
+    #[test]
+    #[ignore]
+    fn mines_are_spread_uniformly() {
+        for difficulty in DIFFICULTIES.iter() {
+            let distribution = mine_distribution(difficulty, 100_000);
+            let min = distribution.counts.iter().min().unwrap_or(&0);
+            let max = distribution.counts.iter().max().unwrap_or(&0);
+            let z_score = distribution.z_score();
+
+            println!(
+                "{name}: {boards} boards, expected {expected:.1} mines per cell (min {min}, max {max}), \
+                 chi² {chi_squared:.1} with {dof} degrees of freedom, z {z_score:.2}",
+                name = difficulty.name,
+                boards = distribution.boards,
+                expected = distribution.expected,
+                min = min,
+                max = max,
+                chi_squared = distribution.chi_squared,
+                dof = distribution.degrees_of_freedom,
+                z_score = z_score,
+            );
+
+            assert!(
+                z_score.abs() < 4.0,
+                "{} mines are not spread uniformly",
+                difficulty.name
+            );
+        }
+    }
+}
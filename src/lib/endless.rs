@@ -0,0 +1,563 @@
+//! Endless mode plays on a board without edges. Cells have signed coordinates and are stored in
+//! square chunks. The mines of a chunk come from the game seed and the chunk's coordinates alone,
+//! so a chunk is only generated once the player gets near it and always comes out the same.
+//!
+//! The frontend still sees a bounded board of `Position`s: the explored chunks plus a margin of
+//! hidden ones, which grows as the player clears towards its edges. It stops growing `REACH`
+//! chunks out from the middle, where it gets as large as a `Position` can address; past that
+//! there are no cells.
+
+use crate::{
+    lib::{
+        engine::Event,
+        game::{Field, Game},
+        position::Position,
+        state::State,
+    },
+    rand::{seq::SliceRandom, SeedableRng},
+    rand_chacha::ChaCha8Rng,
+};
+use std::collections::{HashMap, VecDeque};
+
+pub const CHUNK_SIZE: i32 = 16;
+pub const CHUNK_MINES: usize = 40;
+const CHUNK_AREA: usize = (CHUNK_SIZE * CHUNK_SIZE) as usize;
+/// Chunks kept on the board around every revealed cell, so there is always more board to clear.
+const MARGIN: i32 = 1;
+/// Chunks the board reaches out from the middle in each direction.
+const REACH: i32 = (u16::MAX as i32 / CHUNK_SIZE - 1) / 2;
+
+#[derive(Debug, Copy, Clone, Default, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Coord(pub i32, pub i32);
+
+impl Coord {
+    pub fn chunk(self) -> Coord {
+        Coord(self.0.div_euclid(CHUNK_SIZE), self.1.div_euclid(CHUNK_SIZE))
+    }
+
+    pub fn neighbours(self) -> impl Iterator<Item = Coord> {
+        (-1..=1)
+            .flat_map(move |dy| (-1..=1).map(move |dx| Coord(self.0 + dx, self.1 + dy)))
+            .filter(move |coord| *coord != self)
+    }
+
+    /// Whether the cell is within `REACH` of the middle chunk.
+    fn exists(self) -> bool {
+        let chunk = self.chunk();
+
+        chunk.0.abs() <= REACH && chunk.1.abs() <= REACH
+    }
+
+    /// Index of the cell within its chunk.
+    fn offset(self) -> usize {
+        (self.1.rem_euclid(CHUNK_SIZE) * CHUNK_SIZE + self.0.rem_euclid(CHUNK_SIZE)) as usize
+    }
+
+    /// Cells of the chunk at these chunk coordinates, in offset order.
+    fn cells(self) -> impl Iterator<Item = Coord> {
+        let (left, top) = (self.0 * CHUNK_SIZE, self.1 * CHUNK_SIZE);
+
+        (0..CHUNK_SIZE).flat_map(move |y| (0..CHUNK_SIZE).map(move |x| Coord(left + x, top + y)))
+    }
+}
+
+/// Mines of one chunk, drawn from a stream of the seed picked by the chunk's coordinates. The
+/// cells around the first reveal never hold a mine, so every game opens up.
+fn chunk_mines(seed: u64, chunk: Coord, start: Coord) -> Vec<bool> {
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    rng.set_stream((chunk.0 as u32 as u64) << 32 | chunk.1 as u32 as u64);
+
+    let mut cells = chunk
+        .cells()
+        .filter(|coord| (coord.0 - start.0).abs() > 1 || (coord.1 - start.1).abs() > 1)
+        .collect::<Vec<Coord>>();
+
+    let count = CHUNK_MINES.min(cells.len());
+    let (mines, _) = cells.partial_shuffle(&mut rng, count);
+
+    let mut layout = vec![false; CHUNK_AREA];
+    for mine in mines.iter() {
+        layout[mine.offset()] = true;
+    }
+
+    layout
+}
+
+#[derive(Debug, Clone)]
+pub struct Endless {
+    start: Option<Coord>,
+    mines: HashMap<Coord, Vec<bool>>,
+    chunks: HashMap<Coord, Vec<Field>>,
+    /// First and last chunk on the board, top left to bottom right.
+    first: Coord,
+    last: Coord,
+    /// Safe cells revealed so far, which is the score.
+    pub cleared: u64,
+}
+
+impl Endless {
+    pub fn new() -> Self {
+        Self {
+            start: None,
+            mines: HashMap::new(),
+            chunks: HashMap::new(),
+            first: Coord(-MARGIN, -MARGIN),
+            last: Coord(MARGIN, MARGIN),
+            cleared: 0,
+        }
+    }
+
+    pub fn size(&self) -> (u16, u16) {
+        (
+            ((self.last.0 - self.first.0 + 1) * CHUNK_SIZE) as u16,
+            ((self.last.1 - self.first.1 + 1) * CHUNK_SIZE) as u16,
+        )
+    }
+
+    pub fn coord(&self, position: &Position) -> Coord {
+        Coord(
+            self.first.0 * CHUNK_SIZE + position.0 as i32,
+            self.first.1 * CHUNK_SIZE + position.1 as i32,
+        )
+    }
+
+    /// Where the cell is on the board, if the board reaches that far.
+    pub fn position(&self, coord: Coord) -> Option<Position> {
+        let (width, height) = self.size();
+        let (x, y) = (
+            coord.0 - self.first.0 * CHUNK_SIZE,
+            coord.1 - self.first.1 * CHUNK_SIZE,
+        );
+
+        if x >= 0 && y >= 0 && x < width as i32 && y < height as i32 {
+            Some(Position(x as u16, y as u16))
+        } else {
+            None
+        }
+    }
+
+    /// The cell, if its chunk has been generated.
+    pub fn get(&self, coord: Coord) -> Option<&Field> {
+        self.chunks.get(&coord.chunk())?.get(coord.offset())
+    }
+
+    fn is_mine(&mut self, seed: u64, coord: Coord) -> bool {
+        if !coord.exists() {
+            return false;
+        }
+
+        let start = self.start.unwrap_or_default();
+
+        self.mines
+            .entry(coord.chunk())
+            .or_insert_with(|| chunk_mines(seed, coord.chunk(), start))[coord.offset()]
+    }
+
+    /// The cell, generating its chunk first if needed. Counting the mines around the edge cells
+    /// lays out the mines of the neighbouring chunks too.
+    fn field_mut(&mut self, seed: u64, coord: Coord) -> &mut Field {
+        let chunk = coord.chunk();
+
+        if !self.chunks.contains_key(&chunk) {
+            let mut fields = Vec::with_capacity(CHUNK_AREA);
+            for cell in chunk.cells() {
+                let is_mine = self.is_mine(seed, cell);
+                let mines_around = cell
+                    .neighbours()
+                    .filter(|neighbour| self.is_mine(seed, *neighbour))
+                    .count() as u16;
+
+                fields.push(Field {
+                    is_mine,
+                    mines_around,
+                    ..Field::default()
+                });
+            }
+
+            self.chunks.insert(chunk, fields);
+        }
+
+        &mut self
+            .chunks
+            .get_mut(&chunk)
+            .expect("chunk was just generated")[coord.offset()]
+    }
+
+    /// Reveals the cell and floods out from it over cells without mines around them. Returns the
+    /// cells revealed and the mine hit, if any.
+    fn open(&mut self, seed: u64, coord: Coord) -> (Vec<Coord>, Option<Coord>) {
+        let mut revealed = vec![];
+        let mut queue = VecDeque::new();
+        queue.push_back(coord);
+
+        while let Some(coord) = queue.pop_front() {
+            if !coord.exists() {
+                continue;
+            }
+
+            let field = self.field_mut(seed, coord);
+
+            if field.is_clicked || field.is_flagged {
+                continue;
+            }
+
+            field.is_clicked = true;
+            field.is_question = false;
+
+            if field.is_mine {
+                return (revealed, Some(coord));
+            }
+
+            if field.mines_around == 0 {
+                queue.extend(coord.neighbours());
+            }
+
+            self.cleared += 1;
+            revealed.push(coord);
+        }
+
+        (revealed, None)
+    }
+
+    /// Grows the board to keep a margin of chunks around the cell, as far as the board reaches.
+    fn extend(&mut self, coord: Coord) {
+        let chunk = coord.chunk();
+
+        self.first = Coord(
+            self.first.0.min((chunk.0 - MARGIN).max(-REACH)),
+            self.first.1.min((chunk.1 - MARGIN).max(-REACH)),
+        );
+        self.last = Coord(
+            self.last.0.max((chunk.0 + MARGIN).min(REACH)),
+            self.last.1.max((chunk.1 + MARGIN).min(REACH)),
+        );
+    }
+
+    fn fields(&self) -> impl Iterator<Item = (Coord, &Field)> {
+        self.chunks
+            .iter()
+            .flat_map(|(chunk, fields)| chunk.cells().zip(fields.iter()))
+    }
+}
+
+impl Game {
+    /// Switches between endless and bounded play; the next new game starts in that mode.
+    pub fn set_endless(&mut self, endless: bool) {
+        self.endless = if endless { Some(Endless::new()) } else { None };
+
+        if let Some(endless) = &self.endless {
+            let (width, height) = endless.size();
+            self.width = width;
+            self.height = height;
+        }
+    }
+
+    pub(crate) fn reveal_endless(&mut self, position: Position) -> Vec<Event> {
+        let (seed, started) = (self.seed, self.state != State::NotStarted);
+
+        if !self.state.is_playable() {
+            return vec![];
+        }
+
+        let endless = match self.endless.as_mut() {
+            Some(endless) => endless,
+            None => return vec![],
+        };
+
+        let coord = endless.coord(&position);
+        if endless
+            .get(coord)
+            .map_or(false, |field| field.is_clicked || field.is_flagged)
+        {
+            return vec![];
+        }
+
+        if !started {
+            endless.start = Some(coord);
+        }
+
+        let (revealed, exploded) = endless.open(seed, coord);
+
+        if !started {
+            self.start_timer();
+        }
+
+        self.endless_moved(revealed, exploded)
+    }
+
+    pub(crate) fn toggle_flag_endless(&mut self, position: Position) -> Vec<Event> {
+        let mut events = vec![];
+        let (seed, question_marks) = (self.seed, self.question_marks);
+
+        // Flags wait for the first reveal, which decides where the mines may go.
+        if self.state != State::Running {
+            return events;
+        }
+
+        let endless = match self.endless.as_mut() {
+            Some(endless) => endless,
+            None => return events,
+        };
+
+        let field = endless.field_mut(seed, endless.coord(&position));
+        if field.is_clicked {
+            return events;
+        }
+
+        if field.is_question {
+            field.is_question = false;
+            events.push(Event::Unquestioned(position));
+        } else if field.is_flagged {
+            field.is_flagged = false;
+            events.push(Event::Unflagged(position));
+
+            if question_marks {
+                field.is_question = true;
+                events.push(Event::Questioned(position));
+            }
+        } else {
+            field.is_flagged = true;
+            events.push(Event::Flagged(position));
+        }
+
+        events
+    }
+
+    pub(crate) fn toggle_question_endless(&mut self, position: Position) -> Vec<Event> {
+        let mut events = vec![];
+        let seed = self.seed;
+
+        if self.state != State::Running {
+            return events;
+        }
+
+        let endless = match self.endless.as_mut() {
+            Some(endless) => endless,
+            None => return events,
+        };
+
+        let field = endless.field_mut(seed, endless.coord(&position));
+        if field.is_clicked {
+            return events;
+        }
+
+        if field.is_question {
+            field.is_question = false;
+            events.push(Event::Unquestioned(position));
+        } else {
+            if field.is_flagged {
+                field.is_flagged = false;
+                events.push(Event::Unflagged(position));
+            }
+
+            field.is_question = true;
+            events.push(Event::Questioned(position));
+        }
+
+        events
+    }
+
+    pub(crate) fn chord_endless(&mut self, position: Position) -> Vec<Event> {
+        let seed = self.seed;
+
+        if self.state != State::Running {
+            return vec![];
+        }
+
+        let endless = match self.endless.as_mut() {
+            Some(endless) => endless,
+            None => return vec![],
+        };
+
+        let coord = endless.coord(&position);
+        let mines_around = match endless.get(coord) {
+            Some(field) if field.is_clicked && !field.is_mine && field.mines_around != 0 => {
+                field.mines_around
+            }
+            _ => return vec![],
+        };
+
+        let flags = coord
+            .neighbours()
+            .filter(|neighbour| {
+                neighbour.exists() && endless.field_mut(seed, *neighbour).is_flagged
+            })
+            .count();
+
+        if flags != mines_around as usize {
+            return vec![];
+        }
+
+        let (mut revealed, mut exploded) = (vec![], None);
+        for neighbour in coord.neighbours() {
+            let (opened, mine) = endless.open(seed, neighbour);
+
+            revealed.extend(opened);
+            exploded = exploded.or(mine);
+        }
+
+        self.endless_moved(revealed, exploded)
+    }
+
+    /// Grows the board around the cells a move revealed and reports the move. Once the board
+    /// has grown every cell is reported again, since the frontend has to redraw it at its new
+    /// size.
+    fn endless_moved(&mut self, revealed: Vec<Coord>, exploded: Option<Coord>) -> Vec<Event> {
+        let mut events = vec![];
+
+        if exploded.is_some() {
+            self.stop(State::Lost);
+        }
+
+        let endless = match self.endless.as_mut() {
+            Some(endless) => endless,
+            None => return events,
+        };
+
+        let first = endless.first;
+        for coord in revealed.iter().chain(exploded.iter()) {
+            endless.extend(*coord);
+        }
+
+        if endless.first != first || endless.size() != (self.width, self.height) {
+            let shift = (
+                ((first.0 - endless.first.0) * CHUNK_SIZE) as u16,
+                ((first.1 - endless.first.1) * CHUNK_SIZE) as u16,
+            );
+
+            let (width, height) = endless.size();
+            self.width = width;
+            self.height = height;
+
+            events = self.board_events();
+            events.push(Event::Shifted(shift.0, shift.1));
+            return events;
+        }
+
+        for coord in revealed {
+            if let (Some(position), Some(field)) = (endless.position(coord), endless.get(coord)) {
+                events.push(Event::Revealed(position, field.mines_around));
+            }
+        }
+
+        if let Some(position) = exploded.and_then(|coord| endless.position(coord)) {
+            events.push(Event::Exploded(position));
+        }
+        events.push(Event::Cleared(endless.cleared));
+
+        if exploded.is_some() {
+            self.endless_mines(&mut events);
+            events.push(Event::Lost);
+        }
+
+        events
+    }
+
+    pub(crate) fn endless_board_events(&self) -> Vec<Event> {
+        let endless = match self.endless.as_ref() {
+            Some(endless) => endless,
+            None => return vec![],
+        };
+
+        let mut events = vec![Event::Resized(self.width, self.height)];
+
+        for (coord, field) in endless.fields() {
+            let position = match endless.position(coord) {
+                Some(position) => position,
+                None => continue,
+            };
+
+            if field.is_clicked && field.is_mine {
+                events.push(Event::Exploded(position));
+            } else if field.is_clicked {
+                events.push(Event::Revealed(position, field.mines_around));
+            } else if field.is_flagged {
+                events.push(Event::Flagged(position));
+            } else if field.is_question {
+                events.push(Event::Questioned(position));
+            }
+        }
+
+        events.push(Event::Playing);
+
+        match self.state {
+            State::Lost => {
+                self.endless_mines(&mut events);
+                events.push(Event::Lost);
+            }
+            State::Paused => events.push(Event::Paused),
+            _ => {}
+        }
+
+        events.push(Event::Cleared(endless.cleared));
+
+        events
+    }
+
+    /// Shows the mines of every chunk generated so far; the rest of the board stays unknown.
+    fn endless_mines(&self, events: &mut Vec<Event>) {
+        let endless = match self.endless.as_ref() {
+            Some(endless) => endless,
+            None => return,
+        };
+
+        for (coord, field) in endless.fields() {
+            let position = match endless.position(coord) {
+                Some(position) => position,
+                None => continue,
+            };
+
+            if field.is_mine && !field.is_flagged && !field.is_clicked {
+                events.push(Event::MineShown(position));
+            } else if !field.is_mine && field.is_flagged {
+                events.push(Event::WrongFlag(position));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chunks_come_out_the_same_for_a_seed() {
+        let start = Coord(3, 3);
+        let mines = chunk_mines(7, Coord(2, -1), start);
+
+        assert_eq!(chunk_mines(7, Coord(2, -1), start), mines);
+        assert_ne!(chunk_mines(7, Coord(-1, 2), start), mines);
+        assert_ne!(chunk_mines(8, Coord(2, -1), start), mines);
+        assert_eq!(
+            mines.iter().filter(|is_mine| **is_mine).count(),
+            CHUNK_MINES
+        );
+    }
+
+    #[test]
+    fn the_start_is_clear_of_mines() {
+        // in a chunk's corner, so the cells around it fall in four chunks
+        let start = Coord(CHUNK_SIZE - 1, CHUNK_SIZE - 1);
+
+        for seed in 0..100 {
+            let mut endless = Endless::new();
+            endless.start = Some(start);
+
+            for coord in start.neighbours().chain(Some(start)) {
+                assert!(!endless.is_mine(seed, coord));
+            }
+        }
+    }
+
+    #[test]
+    fn the_board_stops_growing_where_positions_run_out() {
+        let mut endless = Endless::new();
+        endless.extend(Coord(-1 << 20, -1 << 20));
+        endless.extend(Coord(1 << 20, 1 << 20));
+
+        let side = (2 * REACH + 1) * CHUNK_SIZE;
+        assert!(side <= u16::MAX as i32);
+        assert_eq!(endless.size(), (side as u16, side as u16));
+        assert_eq!(endless.position(Coord(1 << 20, 0)), None);
+    }
+}
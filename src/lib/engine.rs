@@ -0,0 +1,714 @@
+use crate::lib::{
+    first_click::FirstClick,
+    game::{Field, Game},
+    gen_mines,
+    grid::Grid,
+    history::Move,
+    position::Position,
+    solver::gen_no_guess,
+    state::State,
+};
+use std::sync::atomic::AtomicBool;
+
+/// A single change to the board, produced by the rules and rendered by the frontend.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event {
+    Revealed(Position, u16),
+    Exploded(Position),
+    Flagged(Position),
+    Unflagged(Position),
+    Questioned(Position),
+    Unquestioned(Position),
+    MineShown(Position),
+    WrongFlag(Position),
+    Hidden(Position),
+    MinesLeft(i32),
+    /// Safe cells cleared in an endless game.
+    Cleared(u64),
+    /// The board changed size; every cell is hidden until reported again.
+    Resized(u16, u16),
+    /// The board grew up and to the left by this many cells, moving every cell with it.
+    Shifted(u16, u16),
+    Playing,
+    Paused,
+    Resumed,
+    Won,
+    Lost,
+}
+
+impl Game {
+    pub fn reveal(&mut self, position: Position) -> Vec<Event> {
+        self.reveal_placing(position, None)
+    }
+
+    /// Reveals the first cell of a board whose `mines` were laid out elsewhere, such as by a
+    /// no-guess search, placing them as part of the same move so undo takes them back too.
+    pub fn reveal_with_mines(&mut self, position: Position, mines: Vec<Position>) -> Vec<Event> {
+        self.reveal_placing(position, Some(mines))
+    }
+
+    fn reveal_placing(&mut self, position: Position, mines: Option<Vec<Position>>) -> Vec<Event> {
+        if self.endless.is_some() {
+            return self.reveal_endless(position);
+        }
+
+        let mut events = vec![];
+
+        if !self.state.is_playable() {
+            return events;
+        }
+
+        if self
+            .field
+            .get(&position)
+            .map_or(true, |field| field.is_clicked || field.is_flagged)
+        {
+            return events;
+        }
+
+        // The move starts before the clock, so undoing the first one brings back a game that
+        // hasn't started.
+        self.begin_move();
+
+        if self.state == State::NotStarted {
+            self.start_timer();
+        }
+
+        match mines {
+            Some(mines) => self.set_mines(mines),
+            None if !self.mines_placed => {
+                self.place_mines(position, &AtomicBool::new(false), |_| {})
+            }
+            None => {}
+        }
+
+        self.open(position, &mut events);
+        self.settle(&mut events);
+        self.commit_move();
+
+        events
+    }
+
+    pub fn toggle_flag(&mut self, position: Position) -> Vec<Event> {
+        if self.endless.is_some() {
+            return self.toggle_flag_endless(position);
+        }
+
+        let mut events = vec![];
+
+        if !self.state.is_playable() {
+            return events;
+        }
+
+        self.begin_move();
+
+        if self.state == State::NotStarted {
+            self.start_timer();
+        }
+        self.touch(position);
+
+        let question_marks = self.question_marks;
+        let field = match self.field.get_mut(&position) {
+            Some(field) if !field.is_clicked => field,
+            _ => return events,
+        };
+
+        if field.is_question {
+            field.is_question = false;
+            events.push(Event::Unquestioned(position));
+            self.commit_move();
+            return events;
+        }
+
+        if field.is_flagged {
+            field.is_flagged = false;
+            self.flags -= 1;
+            events.push(Event::Unflagged(position));
+
+            if question_marks {
+                field.is_question = true;
+                events.push(Event::Questioned(position));
+            }
+        } else {
+            field.is_flagged = true;
+            self.flags += 1;
+            events.push(Event::Flagged(position));
+        }
+
+        events.push(Event::MinesLeft(self.mines_left()));
+        self.commit_move();
+
+        events
+    }
+
+    /// Marks the cell with a question mark, or clears it, whether or not question marks are part
+    /// of the flag cycle.
+    pub fn toggle_question(&mut self, position: Position) -> Vec<Event> {
+        if self.endless.is_some() {
+            return self.toggle_question_endless(position);
+        }
+
+        let mut events = vec![];
+
+        if !self.state.is_playable() {
+            return events;
+        }
+
+        self.begin_move();
+
+        if self.state == State::NotStarted {
+            self.start_timer();
+        }
+        self.touch(position);
+
+        let field = match self.field.get_mut(&position) {
+            Some(field) if !field.is_clicked => field,
+            _ => return events,
+        };
+
+        if field.is_question {
+            field.is_question = false;
+            events.push(Event::Unquestioned(position));
+        } else if field.is_flagged {
+            field.is_flagged = false;
+            field.is_question = true;
+            self.flags -= 1;
+            events.push(Event::Unflagged(position));
+            events.push(Event::Questioned(position));
+            events.push(Event::MinesLeft(self.mines_left()));
+        } else {
+            field.is_question = true;
+            events.push(Event::Questioned(position));
+        }
+
+        self.commit_move();
+
+        events
+    }
+
+    pub fn chord(&mut self, position: Position) -> Vec<Event> {
+        if self.endless.is_some() {
+            return self.chord_endless(position);
+        }
+
+        let mut events = vec![];
+
+        if self.state != State::Running {
+            return events;
+        }
+
+        let mines_around = match self.field.get(&position) {
+            Some(field) if field.is_clicked && !field.is_mine && field.mines_around != 0 => {
+                field.mines_around
+            }
+            _ => return events,
+        };
+
+        let neighbours = self.field.neighbours(position).collect::<Vec<Position>>();
+        let flags = neighbours
+            .iter()
+            .filter(|position| self.field[position].is_flagged)
+            .count();
+
+        if flags != mines_around as usize {
+            return events;
+        }
+
+        self.begin_move();
+        for position in neighbours {
+            self.open(position, &mut events);
+        }
+        self.settle(&mut events);
+        self.commit_move();
+
+        events
+    }
+
+    /// Mines without a flag on them; negative once there are more flags than mines.
+    pub fn mines_left(&self) -> i32 {
+        self.mine_count as i32 - self.flags as i32
+    }
+
+    /// Lays out the mines for a first reveal at `position`. A no-guess search reports each failed
+    /// attempt to `progress` and stops early once `cancel` is set.
+    pub fn place_mines<F: FnMut(u32)>(
+        &mut self,
+        position: Position,
+        cancel: &AtomicBool,
+        progress: F,
+    ) {
+        let (width, height) = (self.width, self.height);
+        let cells = width as usize * height as usize;
+
+        let mut exclude = match self.first_click {
+            FirstClick::SafeCell => vec![position],
+            FirstClick::SafeOpening => {
+                let mut exclude = self.field.neighbours(position).collect::<Vec<Position>>();
+                exclude.push(position);
+                exclude
+            }
+            FirstClick::Classic => vec![],
+        };
+
+        if cells - exclude.len() < self.mine_count as usize || self.no_guess && exclude.is_empty() {
+            exclude = vec![position];
+        }
+
+        let no_guess = if self.no_guess {
+            gen_no_guess(self, &exclude, position, cancel, progress)
+        } else {
+            None
+        };
+        self.no_guess_failed = self.no_guess && no_guess.is_none();
+
+        let mut mines = no_guess
+            .unwrap_or_else(|| gen_mines(width, height, self.mine_count, self.seed, &exclude));
+
+        if let Some(index) = mines.iter().position(|mine| *mine == position) {
+            let corner = (0..height)
+                .flat_map(|y| (0..width).map(move |x| Position(x, y)))
+                .find(|position| !mines.contains(position));
+
+            if let Some(corner) = corner {
+                mines[index] = corner;
+            }
+        }
+
+        self.set_mines(mines);
+    }
+
+    pub fn set_mines(&mut self, mines: Vec<Position>) {
+        let marked = self
+            .field
+            .iter()
+            .filter(|(_, field)| field.is_flagged || field.is_question)
+            .map(|(position, field)| (position, field.is_flagged, field.is_question))
+            .collect::<Vec<(Position, bool, bool)>>();
+
+        let positions = self.field.positions().collect::<Vec<Position>>();
+        for position in positions {
+            self.touch(position);
+        }
+        self.history.mines = Some(self.mines.clone());
+
+        self.field = Grid::new(self.width, self.height, &mines);
+        for (position, is_flagged, is_question) in marked {
+            if let Some(field) = self.field.get_mut(&position) {
+                field.is_flagged = is_flagged;
+                field.is_question = is_question;
+            }
+        }
+
+        self.mines = mines;
+        self.mines_placed = true;
+    }
+
+    fn open(&mut self, position: Position, events: &mut Vec<Event>) {
+        self.touch(position);
+
+        let field = match self.field.get_mut(&position) {
+            Some(field) if !field.is_clicked && !field.is_flagged => field,
+            _ => return,
+        };
+
+        field.is_clicked = true;
+        field.is_question = false;
+        self.hidden -= 1;
+
+        if field.is_mine {
+            self.stop(State::Lost);
+            events.push(Event::Exploded(position));
+            return;
+        }
+
+        events.push(Event::Revealed(position, field.mines_around));
+
+        if field.mines_around != 0 {
+            return;
+        }
+
+        let touched = &mut self.history.touched;
+        let mut unflagged = 0;
+        let revealed = self.field.flood(position, |position, before| {
+            touched.entry(position).or_insert(*before);
+
+            if before.is_flagged {
+                unflagged += 1;
+                events.push(Event::Unflagged(position));
+            }
+        });
+
+        self.hidden -= revealed.len();
+        for position in revealed {
+            events.push(Event::Revealed(
+                position,
+                self.field[&position].mines_around,
+            ));
+        }
+
+        if unflagged != 0 {
+            self.flags -= unflagged;
+            events.push(Event::MinesLeft(self.mines_left()));
+        }
+    }
+
+    fn settle(&mut self, events: &mut Vec<Event>) {
+        if self.state == State::Lost {
+            self.show_mines(events);
+            events.push(Event::Lost);
+            return;
+        }
+
+        if self.safe_left() != 0 {
+            return;
+        }
+
+        self.stop(State::Won);
+        self.show_mines(events);
+        events.push(Event::MinesLeft(0));
+        events.push(Event::Won);
+    }
+
+    pub(crate) fn stop(&mut self, state: State) {
+        self.state = state;
+        self.clock.stop();
+    }
+
+    pub fn toggle_pause(&mut self) -> Vec<Event> {
+        match self.state {
+            State::Running => {
+                self.stop(State::Paused);
+                vec![Event::Paused]
+            }
+            State::Paused => {
+                self.state = State::Running;
+                self.clock.start();
+                vec![Event::Resumed]
+            }
+            _ => vec![],
+        }
+    }
+
+    pub fn undo(&mut self) -> Vec<Event> {
+        if self.state == State::Paused {
+            return vec![];
+        }
+
+        let step = match self.history.undo.pop() {
+            Some(step) => step,
+            None => return vec![],
+        };
+
+        for (position, before, after) in &step.fields {
+            self.field[position] = *before;
+            self.hidden = self.hidden + after.is_clicked as usize - before.is_clicked as usize;
+            self.flags = self.flags + before.is_flagged as u16 - after.is_flagged as u16;
+        }
+
+        if let Some((before, _)) = &step.mines {
+            self.mines = before.clone();
+            self.mines_placed = !before.is_empty();
+        }
+
+        self.restore(step.state.0);
+        self.assisted = true;
+        self.history.redo.push(step);
+
+        self.board_events()
+    }
+
+    pub fn redo(&mut self) -> Vec<Event> {
+        if self.state == State::Paused {
+            return vec![];
+        }
+
+        let step = match self.history.redo.pop() {
+            Some(step) => step,
+            None => return vec![],
+        };
+
+        for (position, before, after) in &step.fields {
+            self.field[position] = *after;
+            self.hidden = self.hidden + before.is_clicked as usize - after.is_clicked as usize;
+            self.flags = self.flags + after.is_flagged as u16 - before.is_flagged as u16;
+        }
+
+        if let Some((_, after)) = &step.mines {
+            self.mines = after.clone();
+            self.mines_placed = !after.is_empty();
+        }
+
+        self.restore(step.state.1);
+        self.history.undo.push(step);
+
+        self.board_events()
+    }
+
+    fn restore(&mut self, state: State) {
+        self.state = state;
+
+        match state {
+            State::Running => self.clock.start(),
+            State::NotStarted => self.clock.reset(),
+            State::Paused | State::Won | State::Lost => self.clock.stop(),
+        }
+    }
+
+    /// Events that redraw every cell, used when the board changes in ways a move can't express.
+    pub fn board_events(&self) -> Vec<Event> {
+        if self.endless.is_some() {
+            return self.endless_board_events();
+        }
+
+        let mut events = vec![];
+        for (position, field) in self.field.iter() {
+            if field.is_clicked && field.is_mine {
+                events.push(Event::Exploded(position));
+            } else if field.is_clicked {
+                events.push(Event::Revealed(position, field.mines_around));
+            } else {
+                events.push(Event::Hidden(position));
+
+                if field.is_flagged {
+                    events.push(Event::Flagged(position));
+                } else if field.is_question {
+                    events.push(Event::Questioned(position));
+                }
+            }
+        }
+
+        events.push(Event::Playing);
+
+        match self.state {
+            State::Won => {
+                self.show_mines(&mut events);
+                events.push(Event::MinesLeft(0));
+                events.push(Event::Won);
+            }
+            State::Lost => {
+                self.show_mines(&mut events);
+                events.push(Event::MinesLeft(self.mines_left()));
+                events.push(Event::Lost);
+            }
+            State::Paused => {
+                events.push(Event::MinesLeft(self.mines_left()));
+                events.push(Event::Paused);
+            }
+            State::NotStarted | State::Running => events.push(Event::MinesLeft(self.mines_left())),
+        }
+
+        events
+    }
+
+    fn begin_move(&mut self) {
+        self.history.touched.clear();
+        self.history.mines = None;
+        self.history.state = self.state;
+    }
+
+    fn touch(&mut self, position: Position) {
+        if self.history.touched.contains_key(&position) {
+            return;
+        }
+
+        if let Some(field) = self.field.get(&position) {
+            self.history.touched.insert(position, *field);
+        }
+    }
+
+    fn commit_move(&mut self) {
+        let field = &self.field;
+        let mut fields = self
+            .history
+            .touched
+            .drain()
+            .filter_map(|(position, before)| {
+                let after = field.get(&position)?;
+
+                if *after == before {
+                    None
+                } else {
+                    Some((position, before, *after))
+                }
+            })
+            .collect::<Vec<(Position, Field, Field)>>();
+
+        let mines = self
+            .history
+            .mines
+            .take()
+            .map(|before| (before, self.mines.clone()));
+
+        if fields.is_empty() && mines.is_none() {
+            return;
+        }
+
+        fields.sort_by_key(|(position, _, _)| *position);
+
+        self.history.undo.push(Move {
+            fields,
+            mines,
+            state: (self.history.state, self.state),
+        });
+        self.history.redo.clear();
+    }
+
+    fn show_mines(&self, events: &mut Vec<Event>) {
+        for (position, field) in self.field.iter() {
+            if field.is_mine && !field.is_flagged && !field.is_clicked {
+                events.push(Event::MineShown(position));
+            } else if !field.is_mine && field.is_flagged {
+                events.push(Event::WrongFlag(position));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    /// A game on a `width`×`height` board with mines exactly where given.
+    fn game(width: u16, height: u16, mines: &[Position]) -> Game {
+        let mut game = Game::new();
+        game.width = width;
+        game.height = height;
+        game.mine_count = mines.len() as u16;
+        game.new_mines_from_seed(0);
+        game.set_mines(mines.to_vec());
+        game
+    }
+
+    /// A 5×3 board split in two by a column of mines down the middle.
+    fn split_board() -> Game {
+        game(5, 3, &[Position(2, 0), Position(2, 1), Position(2, 2)])
+    }
+
+    #[test]
+    fn reveal_floods_up_to_the_numbers_and_wins_once_only_mines_are_hidden() {
+        let mut game = split_board();
+
+        let events = game.reveal(Position(0, 1));
+        assert!(events.contains(&Event::Revealed(Position(0, 1), 0)));
+        assert!(events.contains(&Event::Revealed(Position(1, 1), 3)));
+        assert!(!events
+            .iter()
+            .any(|event| matches!(event, Event::Revealed(Position(3, _), _))));
+        assert_eq!(game.hidden, 9);
+        assert_eq!(game.safe_left(), 6);
+        assert_eq!(game.state, State::Running);
+
+        let events = game.reveal(Position(4, 1));
+        assert_eq!(game.hidden, 3);
+        assert_eq!(game.safe_left(), 0);
+        assert_eq!(game.state, State::Won);
+        assert_eq!(events.last(), Some(&Event::Won));
+    }
+
+    #[test]
+    fn chording_onto_a_wrong_flag_explodes() {
+        let mut game = game(3, 3, &[Position(0, 0), Position(2, 2)]);
+
+        game.reveal(Position(1, 1));
+        game.toggle_flag(Position(0, 0));
+        game.toggle_flag(Position(2, 0));
+        let events = game.chord(Position(1, 1));
+
+        assert_eq!(game.state, State::Lost);
+        assert!(events.contains(&Event::Exploded(Position(2, 2))));
+        assert!(events.contains(&Event::WrongFlag(Position(2, 0))));
+        assert_eq!(events.last(), Some(&Event::Lost));
+    }
+
+    #[test]
+    fn chording_needs_as_many_flags_as_mines_around() {
+        let mut game = game(3, 3, &[Position(0, 0)]);
+
+        game.reveal(Position(1, 1));
+        assert!(game.chord(Position(1, 1)).is_empty());
+        assert_eq!(game.hidden, 8);
+    }
+
+    #[test]
+    fn undo_and_redo_restore_the_board_exactly() {
+        let mut game = split_board();
+        let snapshot = |game: &Game| (game.field.clone(), game.hidden, game.flags, game.state);
+
+        let mut snapshots = vec![snapshot(&game)];
+        game.toggle_flag(Position(0, 0));
+        snapshots.push(snapshot(&game));
+        game.toggle_flag(Position(2, 0));
+        snapshots.push(snapshot(&game));
+        // the flood clears the flag on (0, 0)
+        game.reveal(Position(0, 1));
+        snapshots.push(snapshot(&game));
+        game.toggle_question(Position(3, 0));
+        snapshots.push(snapshot(&game));
+
+        for expected in snapshots.iter().rev().skip(1) {
+            game.undo();
+            assert_eq!(&snapshot(&game), expected);
+        }
+        assert!(game.undo().is_empty());
+        assert_eq!(game.elapsed(), Duration::from_secs(0));
+
+        for expected in snapshots.iter().skip(1) {
+            game.redo();
+            assert_eq!(&snapshot(&game), expected);
+        }
+        assert!(game.assisted);
+    }
+
+    #[test]
+    fn undoing_the_first_reveal_takes_the_mines_back() {
+        let mut game = Game::new();
+        game.new_mines_from_seed(1);
+
+        game.reveal(Position(4, 4));
+        let mines = game.mines.clone();
+        assert!(game.mines_placed);
+
+        game.undo();
+        assert!(!game.mines_placed);
+        assert_eq!(game.state, State::NotStarted);
+
+        game.redo();
+        assert_eq!(game.mines, mines);
+    }
+
+    #[test]
+    fn mines_left_counts_past_the_range_of_an_i16() {
+        let mut game = Game::new();
+        game.mine_count = 40_000;
+
+        assert_eq!(game.mines_left(), 40_000);
+
+        game.mine_count = 1;
+        game.flags = 40_000;
+        assert_eq!(game.mines_left(), -39_999);
+    }
+
+    #[test]
+    fn undoing_a_reveal_with_mines_takes_them_back() {
+        let mut game = Game::new();
+        game.new_mines_from_seed(1);
+
+        // mines laid out for the same first click on another board, as a no-guess search would
+        let mut other = Game::new();
+        other.new_mines_from_seed(2);
+        other.reveal(Position(4, 4));
+        let mines = other.mines.clone();
+
+        game.reveal_with_mines(Position(4, 4), mines.clone());
+        assert_eq!(game.mines, mines);
+
+        game.undo();
+        assert!(!game.mines_placed);
+        assert_eq!(game.state, State::NotStarted);
+
+        game.redo();
+        assert_eq!(game.mines, mines);
+        assert!(game.field_at(&Position(4, 4)).unwrap().is_clicked);
+    }
+}
@@ -0,0 +1,82 @@
+use gtk::{
+    prelude::*, ApplicationWindow, ButtonsType, DialogFlags, FileChooserAction, FileChooserDialog,
+    FileFilter, MessageDialog, MessageType, ResponseType,
+};
+use std::path::PathBuf;
+
+fn file_dialog(
+    window: &ApplicationWindow,
+    title: &str,
+    action: FileChooserAction,
+) -> Option<PathBuf> {
+    let accept = match action {
+        FileChooserAction::Save => "Save",
+        _ => "Open",
+    };
+    let dialog = FileChooserDialog::with_buttons(
+        Some(title),
+        Some(window),
+        action,
+        &[
+            ("Cancel", ResponseType::Cancel),
+            (accept, ResponseType::Accept),
+        ],
+    );
+
+    let filter = FileFilter::new();
+    filter.set_name(Some("Saved games"));
+    filter.add_pattern("*.msroj");
+    dialog.add_filter(&filter);
+
+    if action == FileChooserAction::Save {
+        dialog.set_do_overwrite_confirmation(true);
+        dialog.set_current_name("game.msroj");
+    }
+
+    let path = match dialog.run() {
+        ResponseType::Accept => dialog.get_filename(),
+        _ => None,
+    };
+
+    unsafe { dialog.destroy() }
+
+    path
+}
+
+pub fn save_dialog(window: &ApplicationWindow) -> Option<PathBuf> {
+    file_dialog(window, "Save Game", FileChooserAction::Save)
+}
+
+pub fn open_dialog(window: &ApplicationWindow) -> Option<PathBuf> {
+    file_dialog(window, "Open Game", FileChooserAction::Open)
+}
+
+pub fn confirm_dialog(window: &ApplicationWindow, message: &str) -> bool {
+    let dialog = MessageDialog::new(
+        Some(window),
+        DialogFlags::MODAL | DialogFlags::DESTROY_WITH_PARENT,
+        MessageType::Question,
+        ButtonsType::YesNo,
+        message,
+    );
+
+    let confirmed = dialog.run() == ResponseType::Yes;
+
+    unsafe { dialog.destroy() }
+
+    confirmed
+}
+
+pub fn error_dialog(window: &ApplicationWindow, message: &str) {
+    let dialog = MessageDialog::new(
+        Some(window),
+        DialogFlags::MODAL | DialogFlags::DESTROY_WITH_PARENT,
+        MessageType::Error,
+        ButtonsType::Close,
+        message,
+    );
+
+    dialog.run();
+
+    unsafe { dialog.destroy() }
+}
@@ -0,0 +1,37 @@
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum FirstClick {
+    SafeCell,
+    SafeOpening,
+    Classic,
+}
+
+pub const FIRST_CLICKS: [FirstClick; 3] = [
+    FirstClick::SafeOpening,
+    FirstClick::SafeCell,
+    FirstClick::Classic,
+];
+
+impl FirstClick {
+    pub fn id(&self) -> &'static str {
+        match self {
+            FirstClick::SafeCell => "safe_cell",
+            FirstClick::SafeOpening => "safe_opening",
+            FirstClick::Classic => "classic",
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            FirstClick::SafeCell => "Safe Cell",
+            FirstClick::SafeOpening => "Safe Opening",
+            FirstClick::Classic => "Classic (Move Mine to Corner)",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        FIRST_CLICKS
+            .iter()
+            .find(|first_click| first_click.id() == id)
+            .copied()
+    }
+}
@@ -1,138 +1,164 @@
-use crate::lib::{gen_mines, position::Position};
-use std::{collections::HashMap, time::Instant};
-
-pub type FieldMap = HashMap<Position, Field>;
-
-fn gen(size: u16) -> (Vec<Position>, FieldMap) {
-    let mines = gen_mines(size);
-
-    let mut field = HashMap::new();
-
-    for x in 0..size {
-        for y in 0..size {
-            let pos = Position(x, y);
-            let is_mine = mines.contains(&pos);
-            field.insert(
-                pos,
-                Field {
-                    is_mine,
-                    is_clicked: false,
-                    is_flagged: false,
-                    mines_around: around(&pos, &mines),
-                    adjecent_empty: adjecent_empty(&pos, &mines),
-                },
-            );
-        }
+use crate::lib::{
+    clock::Clock,
+    difficulty::{Difficulty, BEGINNER},
+    endless::Endless,
+    first_click::FirstClick,
+    gen_seed,
+    grid::Grid,
+    history::History,
+    position::Position,
+    state::State,
+};
+use std::time::Duration;
+
+pub fn check_board(width: u16, height: u16, mines: u16) -> Result<(), String> {
+    let cells = width as u32 * height as u32;
+
+    if width < 2 || height < 2 {
+        return Err("The board must be at least 2×2".to_string());
+    }
+
+    if mines == 0 {
+        return Err("There must be at least one mine".to_string());
     }
 
-    (mines, field)
+    if mines as u32 >= cells {
+        return Err(format!("There must be fewer mines than cells ({})", cells));
+    }
+
+    Ok(())
 }
 
-#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+#[derive(Debug, Copy, Clone, Default, Hash, PartialEq, Eq)]
 pub struct Field {
     pub is_mine: bool,
     pub is_clicked: bool,
     pub is_flagged: bool,
+    pub is_question: bool,
     pub mines_around: u16,
-    pub adjecent_empty: Vec<Position>,
-}
-
-impl Field {
-    pub fn mines_around_class_name(&self) -> String {
-        format!(
-            "btn_nearby_{}",
-            match self.mines_around {
-                1 => "one",
-                2 => "two",
-                _ => "multiple",
-            }
-        )
-        .to_string()
-    }
 }
 
+#[derive(Debug, Clone)]
 pub struct Game {
-    pub size: u16,
+    pub width: u16,
+    pub height: u16,
+    pub mine_count: u16,
+    pub seed: u64,
+    pub first_click: FirstClick,
+    pub no_guess: bool,
+    /// Set when no guess-free board turned up for a no-guess game, which then got a board that
+    /// may need guessing.
+    pub no_guess_failed: bool,
+    pub question_marks: bool,
+    pub assisted: bool,
+    pub history: History,
+    pub mines_placed: bool,
     pub mines: Vec<Position>,
-    pub field: FieldMap,
-    pub active: bool,
-    pub ended: bool,
-    pub time: Instant,
+    pub field: Grid,
+    /// Cells not revealed yet, kept up to date by every move so a win is spotted without
+    /// scanning the board: it comes once only the mines are left hidden.
+    pub hidden: usize,
+    pub flags: u16,
+    pub state: State,
+    pub clock: Clock,
+    /// Set while playing on an endless board, which then stands in for `field`.
+    pub endless: Option<Endless>,
 }
 
 impl Game {
     pub fn new() -> Self {
-        let size = 9;
-        let (mines, field) = gen(size);
+        let (width, height, mine_count) = (BEGINNER.width, BEGINNER.height, BEGINNER.mines);
+        let seed = gen_seed();
+        let mines = vec![];
+        let field = Grid::new(width, height, &mines);
 
         Self {
-            size,
+            width,
+            height,
+            mine_count,
+            seed,
+            first_click: FirstClick::SafeOpening,
+            no_guess: false,
+            no_guess_failed: false,
+            question_marks: false,
+            assisted: false,
+            history: History::default(),
+            mines_placed: false,
             mines,
             field,
-            active: false,
-            ended: false,
-            time: Instant::now(),
+            hidden: width as usize * height as usize,
+            flags: 0,
+            state: State::NotStarted,
+            clock: Clock::default(),
+            endless: None,
         }
     }
 
-    pub fn start_timer(&mut self) {
-        self.active = true;
-        self.time = Instant::now();
+    pub fn difficulty(&self) -> Option<&'static Difficulty> {
+        Difficulty::find(self.width, self.height, self.mine_count)
     }
 
-    pub fn new_mines(&mut self) {
-        let (mines, field) = gen(self.size);
+    pub fn field_at(&self, position: &Position) -> Option<Field> {
+        match &self.endless {
+            Some(endless) => Some(
+                endless
+                    .get(endless.coord(position))
+                    .copied()
+                    .unwrap_or_default(),
+            ),
+            None => self.field.get(position).copied(),
+        }
+    }
 
-        self.mines = mines;
-        self.field = field;
-        self.active = false;
-        self.ended = false;
-        self.time = Instant::now();
+    pub fn start_timer(&mut self) {
+        self.state = State::Running;
+        self.clock.reset();
+        self.clock.start();
     }
-}
 
-fn p_around(pos: &Position) -> Vec<(u16, u16)> {
-    let (x, y) = (pos.0, pos.1);
-    let mut points = vec![(x, y), (x + 1, y), (x, y + 1), (x + 1, y + 1)];
+    /// Time played so far; the clock stands still while the game is paused or over.
+    pub fn elapsed(&self) -> Duration {
+        self.clock.elapsed()
+    }
 
-    let (pos_x, pos_y) = (x > 0, y > 0);
+    /// Safe cells that still have to be revealed.
+    pub fn safe_left(&self) -> usize {
+        self.hidden.saturating_sub(self.mine_count as usize)
+    }
 
-    if pos_y {
-        if pos_x {
-            points.push((x - 1, y - 1));
+    /// The time a finished game took, to the millisecond.
+    pub fn final_time(&self) -> Option<Duration> {
+        if self.state.is_over() {
+            Some(Duration::from_millis(self.elapsed().as_millis() as u64))
+        } else {
+            None
         }
-        points.push((x, y - 1));
-        points.push((x + 1, y - 1));
     }
 
-    if pos_x {
-        points.push((x - 1, y));
-        points.push((x - 1, y + 1));
+    pub fn new_mines(&mut self) {
+        self.new_mines_from_seed(gen_seed());
     }
 
-    points
-}
+    pub fn new_mines_from_seed(&mut self, seed: u64) {
+        if self.endless.is_some() {
+            let endless = Endless::new();
+            let (width, height) = endless.size();
 
-fn around(pos: &Position, mines: &Vec<Position>) -> u16 {
-    p_around(&pos).iter().fold(0, |total, (x, y)| {
-        if mines.contains(&Position(*x, *y)) {
-            total + 1
-        } else {
-            total
+            self.width = width;
+            self.height = height;
+            self.endless = Some(endless);
         }
-    })
-}
 
-fn adjecent_empty(pos: &Position, mines: &Vec<Position>) -> Vec<Position> {
-    p_around(&pos)
-        .iter()
-        .filter_map(|(x, y)| {
-            let position = Position(*x, *y);
-            if mines.contains(&position) {
-                None
-            } else {
-                Some(position.to_owned())
-            }
-        })
-        .collect()
+        self.seed = seed;
+        self.assisted = false;
+        self.no_guess_failed = false;
+        self.history.clear();
+        self.mines_placed = false;
+        self.mines = vec![];
+        self.field = Grid::new(self.width, self.height, &self.mines);
+        self.hidden = self.field.len();
+        self.flags = 0;
+        self.state = State::NotStarted;
+        self.clock.reset();
+    }
 }
@@ -0,0 +1,184 @@
+use crate::lib::{game::Field, position::Position};
+use std::ops::{Index, IndexMut};
+
+/// The cells of a board in one row-major `Vec`, so `Position(x, y)` lives at `y * width + x`.
+/// Neighbours are worked out from the position when needed rather than stored per cell.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid {
+    width: u16,
+    height: u16,
+    cells: Vec<Field>,
+}
+
+impl Grid {
+    pub fn new(width: u16, height: u16, mines: &[Position]) -> Self {
+        let mut grid = Self {
+            width,
+            height,
+            cells: vec![Field::default(); width as usize * height as usize],
+        };
+
+        for mine in mines {
+            if let Some(index) = grid.index(mine) {
+                grid.cells[index].is_mine = true;
+
+                for position in mine.neighbours(width, height) {
+                    grid[&position].mines_around += 1;
+                }
+            }
+        }
+
+        grid
+    }
+
+    pub fn width(&self) -> u16 {
+        self.width
+    }
+
+    pub fn height(&self) -> u16 {
+        self.height
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn index(&self, position: &Position) -> Option<usize> {
+        if position.0 < self.width && position.1 < self.height {
+            Some(position.1 as usize * self.width as usize + position.0 as usize)
+        } else {
+            None
+        }
+    }
+
+    pub fn position(&self, index: usize) -> Position {
+        let width = self.width as usize;
+
+        Position((index % width) as u16, (index / width) as u16)
+    }
+
+    pub fn get(&self, position: &Position) -> Option<&Field> {
+        self.index(position).map(|index| &self.cells[index])
+    }
+
+    pub fn get_mut(&mut self, position: &Position) -> Option<&mut Field> {
+        let index = self.index(position)?;
+
+        Some(&mut self.cells[index])
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &Field> {
+        self.cells.iter()
+    }
+
+    pub fn positions(&self) -> impl Iterator<Item = Position> {
+        let width = self.width;
+
+        (0..self.height).flat_map(move |y| (0..width).map(move |x| Position(x, y)))
+    }
+
+    /// Every cell with its position, top row first.
+    pub fn iter(&self) -> impl Iterator<Item = (Position, &Field)> {
+        self.positions().zip(self.cells.iter())
+    }
+
+    pub fn neighbours(&self, position: Position) -> impl Iterator<Item = Position> {
+        position.neighbours(self.width, self.height)
+    }
+
+    /// Reveals the opening around the empty cell at `start` breadth first, clearing any marks in
+    /// it, and returns the cells it revealed in the order they were reached. `reveal` sees each
+    /// cell just before it is revealed.
+    pub fn flood<F: FnMut(Position, &Field)>(
+        &mut self,
+        start: Position,
+        mut reveal: F,
+    ) -> Vec<Position> {
+        let (width, height) = (self.width, self.height);
+        let mut queue = match self.index(&start) {
+            Some(index) => vec![index],
+            None => return vec![],
+        };
+
+        let mut next = 0;
+        while next < queue.len() {
+            let index = queue[next];
+            next += 1;
+
+            if self.cells[index].mines_around != 0 {
+                continue;
+            }
+
+            for position in self.position(index).neighbours(width, height) {
+                let neighbour = position.1 as usize * width as usize + position.0 as usize;
+                let field = &mut self.cells[neighbour];
+
+                if field.is_clicked || field.is_mine {
+                    continue;
+                }
+
+                reveal(position, field);
+                field.is_clicked = true;
+                field.is_flagged = false;
+                field.is_question = false;
+                queue.push(neighbour);
+            }
+        }
+
+        queue
+            .into_iter()
+            .skip(1)
+            .map(|index| self.position(index))
+            .collect()
+    }
+}
+
+impl Index<&Position> for Grid {
+    type Output = Field;
+
+    fn index(&self, position: &Position) -> &Field {
+        self.get(position).expect("position is outside the board")
+    }
+}
+
+impl IndexMut<&Position> for Grid {
+    fn index_mut(&mut self, position: &Position) -> &mut Field {
+        self.get_mut(position)
+            .expect("position is outside the board")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_the_mines_around_each_cell() {
+        let grid = Grid::new(3, 2, &[Position(0, 0), Position(2, 1)]);
+        let counts = grid
+            .values()
+            .map(|field| field.mines_around)
+            .collect::<Vec<u16>>();
+
+        assert_eq!(counts, vec![0, 2, 1, 1, 2, 0]);
+        assert!(grid[&Position(2, 1)].is_mine);
+        assert_eq!(grid.get(&Position(3, 0)), None);
+    }
+
+    #[test]
+    fn flood_reveals_breadth_first_and_clears_marks() {
+        let mut grid = Grid::new(4, 1, &[Position(3, 0)]);
+        grid[&Position(0, 0)].is_clicked = true;
+        grid[&Position(1, 0)].is_flagged = true;
+
+        let mut seen = vec![];
+        let revealed = grid.flood(Position(0, 0), |position, before| {
+            seen.push((position, before.is_flagged));
+        });
+
+        assert_eq!(revealed, vec![Position(1, 0), Position(2, 0)]);
+        assert_eq!(seen, vec![(Position(1, 0), true), (Position(2, 0), false)]);
+        assert!(grid[&Position(1, 0)].is_clicked && !grid[&Position(1, 0)].is_flagged);
+        assert!(!grid[&Position(3, 0)].is_clicked);
+    }
+}
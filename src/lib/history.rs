@@ -0,0 +1,27 @@
+use crate::lib::{game::Field, position::Position, state::State};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone)]
+pub struct Move {
+    pub fields: Vec<(Position, Field, Field)>,
+    pub mines: Option<(Vec<Position>, Vec<Position>)>,
+    pub state: (State, State),
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct History {
+    pub undo: Vec<Move>,
+    pub redo: Vec<Move>,
+    pub touched: HashMap<Position, Field>,
+    pub mines: Option<Vec<Position>>,
+    pub state: State,
+}
+
+impl History {
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.touched.clear();
+        self.mines = None;
+    }
+}
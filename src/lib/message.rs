@@ -1,11 +1,19 @@
-use crate::lib::{block::Block, position::Position};
+use crate::lib::{board::Zoom, position::Position};
 
 #[derive(Debug, Clone)]
 pub enum Message {
     SetTime(String),
-    SetMines(String),
-    UpdateButton(Position, Block, bool),
-    End,
+    UpdateButton(Position, bool),
+    Chord(Position),
+    Question(Position),
+    Undo,
+    Redo,
+    Redraw,
+    Pause,
+    Zoom(Zoom, Option<(f64, f64)>),
     Reset,
+    Seed(u64),
+    Generating(u32),
+    MinesPlaced(u32, Position, Vec<Position>, bool),
     Quit,
 }
@@ -0,0 +1,119 @@
+use crate::lib::{game::Game, position::Position, state::State};
+
+use gdk::EventMask;
+use gtk::{prelude::*, DrawingArea};
+
+/// Length of the longer side of the minimap.
+pub const MINIMAP_SIZE: f64 = 160.0;
+
+const HIDDEN: (f64, f64, f64) = (0.118, 0.141, 0.165);
+const REVEALED: (f64, f64, f64) = (0.286, 0.310, 0.333);
+const FLAGGED: (f64, f64, f64) = (0.894, 0.722, 0.027);
+const EXPLODED: (f64, f64, f64) = (0.941, 0.310, 0.310);
+
+/// An overview of the whole board in a corner of the window, with the part in view outlined.
+/// It is only shown while the board doesn't fit in the window.
+#[derive(Clone, Debug)]
+pub struct Minimap {
+    pub area: DrawingArea,
+}
+
+impl Minimap {
+    pub fn new() -> Self {
+        let area = DrawingArea::new();
+        area.set_no_show_all(true);
+        area.set_halign(gtk::Align::End);
+        area.set_valign(gtk::Align::End);
+        area.set_margin_end(12);
+        area.set_margin_bottom(12);
+        area.add_events(
+            EventMask::BUTTON_PRESS_MASK
+                | EventMask::BUTTON1_MOTION_MASK
+                | EventMask::POINTER_MOTION_HINT_MASK,
+        );
+
+        Self { area }
+    }
+
+    /// Minimap pixels per board cell.
+    fn scale(width: u16, height: u16) -> f64 {
+        MINIMAP_SIZE / width.max(height).max(1) as f64
+    }
+
+    /// Sizes the minimap for the board and shows it if the part in view isn't all of it.
+    pub fn update(&self, width: u16, height: u16, fits: bool) {
+        let scale = Self::scale(width, height);
+        let size = (
+            (width as f64 * scale).ceil() as i32,
+            (height as f64 * scale).ceil() as i32,
+        );
+
+        if self.area.get_size_request() != size {
+            self.area.set_size_request(size.0, size.1);
+        }
+
+        self.area.set_visible(!fits);
+        self.area.queue_draw();
+    }
+
+    /// Paints every cell of the game and outlines the view, given as fractions of the board:
+    /// left, top, width and height. A paused game's cells stay covered, as on the board.
+    pub fn paint(&self, cr: &cairo::Context, game: &Game, view: (f64, f64, f64, f64)) {
+        let (width, height) = (game.width, game.height);
+        let scale = Self::scale(width, height);
+
+        cr.set_source_rgb(HIDDEN.0, HIDDEN.1, HIDDEN.2);
+        cr.paint();
+
+        if game.state != State::Paused {
+            for y in 0..height {
+                for x in 0..width {
+                    let field = match game.field_at(&Position(x, y)) {
+                        Some(field) => field,
+                        None => continue,
+                    };
+
+                    let color = if field.is_clicked && field.is_mine {
+                        EXPLODED
+                    } else if field.is_clicked {
+                        REVEALED
+                    } else if field.is_flagged || field.is_question {
+                        FLAGGED
+                    } else {
+                        continue;
+                    };
+
+                    cr.set_source_rgb(color.0, color.1, color.2);
+                    cr.rectangle(x as f64 * scale, y as f64 * scale, scale, scale);
+                    cr.fill();
+                }
+            }
+        }
+
+        let (map_width, map_height) = (width as f64 * scale, height as f64 * scale);
+        let (left, top, view_width, view_height) = view;
+
+        cr.set_source_rgb(1.0, 1.0, 1.0);
+        cr.set_line_width(1.0);
+        cr.rectangle(
+            (left * map_width).round() + 0.5,
+            (top * map_height).round() + 0.5,
+            (view_width * map_width).round() - 1.0,
+            (view_height * map_height).round() - 1.0,
+        );
+        cr.stroke();
+    }
+
+    /// The point under the pointer as fractions of the board.
+    pub fn fraction_at(&self, x: f64, y: f64) -> (f64, f64) {
+        let (width, height) = (
+            self.area.get_allocated_width().max(1) as f64,
+            self.area.get_allocated_height().max(1) as f64,
+        );
+
+        (
+            (x / width).max(0.0).min(1.0),
+            (y / height).max(0.0).min(1.0),
+        )
+    }
+}
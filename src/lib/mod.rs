@@ -1,156 +1,81 @@
 pub mod application;
-pub mod block;
+pub mod autosave;
+pub mod bindings;
+pub mod bindings_dialog;
+pub mod board;
+pub mod clock;
+pub mod custom_dialog;
+pub mod difficulty;
+#[cfg(test)]
+pub mod distribution;
+pub mod endless;
+pub mod engine;
+pub mod file_dialog;
+pub mod first_click;
 pub mod game;
-pub mod gen_mine_grid;
+pub mod grid;
+pub mod history;
 pub mod message;
+pub mod minimap;
 pub mod position;
+pub mod render;
+pub mod save;
+pub mod seed_dialog;
+pub mod solver;
+pub mod state;
 pub mod ui;
 pub mod widget;
+pub mod zoom;
 
 use crate::{
-    lib::{
-        game::{Field, FieldMap, Game},
-        position::Position,
-        widget::Widget,
-    },
-    rand::Rng,
+    lib::position::Position,
+    rand::{seq::SliceRandom, Rng, SeedableRng},
+    rand_chacha::ChaCha8Rng,
 };
 
-use gtk::prelude::*;
-use std::{cell::RefCell, collections::HashSet, mem, rc::Rc};
-
-pub fn gen_mines(size: u16) -> Vec<Position> {
-    let mine = || -> Position {
-        let mut rng = rand::thread_rng();
-        Position(rng.gen_range(0, size - 1), rng.gen_range(0, size - 1))
-    };
-
-    let mut mines = vec![];
-
-    let mut total_mines = size + 1;
-
-    if total_mines > 10 {
-        total_mines = 10 + ((total_mines - 10) * 3);
-    }
-
-    while mines.len() < total_mines as usize {
-        let mine = mine();
-        if mines.contains(&mine) {
-            continue;
-        }
-        mines.push(mine.to_owned())
-    }
-
-    mines
+pub fn gen_seed() -> u64 {
+    rand::thread_rng().gen()
 }
 
-pub fn get_tiles_around(pos: &Position, block: &Field, field_map: &FieldMap) -> HashSet<Position> {
-    let mut around: HashSet<Position> = HashSet::new();
-    if block.mines_around != 0 {
-        return around;
-    }
-
-    let mut adj = vec![block.to_owned()];
-    let mut checked = HashSet::new();
-    loop {
-        let mut new_adj = vec![];
-        adj.iter().for_each(|field| {
-            if checked.contains(field) {
-                return;
-            }
-            checked.insert(field.to_owned());
-
-            if field.is_mine || field.mines_around != 0 {
-                return;
-            }
-
-            &field.adjecent_empty.iter().for_each(|pos| {
-                if around.contains(&pos) {
-                    return;
-                }
-
-                around.insert(pos.to_owned());
-                if let Some(field) = field_map.get(pos) {
-                    if !field.is_mine || field.mines_around == 0 {
-                        new_adj.push(field.to_owned());
-                    }
-                }
-            });
-        });
-
-        if new_adj.is_empty() {
-            break;
-        }
-
-        let _ = mem::replace(&mut adj, new_adj);
-    }
+/// Lays out the mines from the seed alone, then moves any that fall in `exclude` to the next
+/// free cells in the seed's shuffled order, so where the first click lands only changes the mines
+/// around it and a seed always replays the same board.
+pub fn gen_mines(
+    width: u16,
+    height: u16,
+    total_mines: u16,
+    seed: u64,
+    exclude: &[Position],
+) -> Vec<Position> {
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    let mut cells = (0..height)
+        .flat_map(|y| (0..width).map(move |x| Position(x, y)))
+        .collect::<Vec<Position>>();
+
+    let free = cells
+        .iter()
+        .filter(|position| !exclude.contains(position))
+        .count();
+    let total_mines = (total_mines as usize).min(free);
+
+    cells.shuffle(&mut rng);
+    let (rest, mines) = cells.split_at(cells.len() - total_mines);
+
+    let mut moved_to = rest
+        .iter()
+        .rev()
+        .filter(|position| !exclude.contains(position));
 
-    around
-}
-
-fn flood(flood_widget: Rc<Widget>, flood_game: Rc<RefCell<Game>>, position: &Position) {
-    let mut positions: HashSet<Position> = HashSet::new();
-
-    {
-        let game = flood_game.borrow();
-        if let Some(field) = game.field.get(&position) {
-            let field_ref = game.field.to_owned();
-            let _ = mem::replace(
-                &mut positions,
-                get_tiles_around(&position, &field, &field_ref),
-            );
-        }
-    }
-
-    if positions.is_empty() {
-        return;
-    }
-
-    let mut game = flood_game.borrow_mut();
-    let mut mines_modified = 0;
-    for position in positions {
-        let (mut label, mut class_names) = (" ".to_string(), vec![]);
-
-        if let Some(field) = game.field.get_mut(&position) {
-            field.is_clicked = true;
-
-            if field.mines_around != 0 {
-                label = field.mines_around.to_string();
-                class_names.push("btn_nearby".to_string());
-                class_names.push(field.mines_around_class_name());
+    mines
+        .iter()
+        .map(|mine| {
+            if exclude.contains(mine) {
+                *moved_to
+                    .next()
+                    .expect("a free cell for every excluded mine")
             } else {
-                class_names.push("btn_empty".to_string());
-            }
-        }
-
-        if let Some(block) = flood_widget
-            .mines
-            .borrow_mut()
-            .get(&Position(position.0, position.1))
-        {
-            let button = &block.0;
-            button.set_label(&label);
-            button.set_can_focus(false);
-            let ctx = button.get_style_context();
-
-            if ctx.has_class("btn_flag") {
-                mines_modified += 1;
+                *mine
             }
-
-            clear_classes!(ctx, "btn_");
-            for class in class_names {
-                ctx.add_class(&class);
-            }
-        }
-    }
-
-    if mines_modified != 0 {
-        let mut mines: i16 = flood_widget
-            .label_mines_left
-            .get_label()
-            .parse()
-            .unwrap_or(0);
-        mines += mines_modified;
-        flood_widget.label_mines_left.set_label(&mines.to_string());
-    }
+        })
+        .collect()
 }
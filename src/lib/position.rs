@@ -3,6 +3,21 @@ use std::cmp::Ordering::{self, Equal, Greater, Less};
 #[derive(Debug, Copy, Clone, Hash)]
 pub struct Position(pub u16, pub u16);
 
+impl Position {
+    /// The up to eight cells around this one that lie on a `width`×`height` board.
+    pub fn neighbours(&self, width: u16, height: u16) -> impl Iterator<Item = Position> {
+        let (x, y) = (self.0 as i32, self.1 as i32);
+        let (width, height) = (width as i32, height as i32);
+
+        (-1..=1)
+            .flat_map(move |dy| (-1..=1).map(move |dx| (x + dx, y + dy)))
+            .filter(move |&(nx, ny)| {
+                (nx, ny) != (x, y) && nx >= 0 && ny >= 0 && nx < width && ny < height
+            })
+            .map(|(nx, ny)| Position(nx as u16, ny as u16))
+    }
+}
+
 impl PartialEq for Position {
     fn eq(&self, other: &Self) -> bool {
         self.0 == other.0 && self.1 == other.1
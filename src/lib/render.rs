@@ -0,0 +1,69 @@
+use crate::lib::{board::Look, engine::Event, widget::Widget};
+
+use gtk::prelude::*;
+
+/// Pads the count to three digits like the classic counter, keeping the sign when over-flagged.
+/// Counts the counter can't hold stop at its ends.
+fn mines_left_label(mines_left: i32) -> String {
+    let mines_left = mines_left.max(-99).min(999);
+
+    if mines_left < 0 {
+        format!("-{:0>2}", -mines_left)
+    } else {
+        format!("{:0>3}", mines_left)
+    }
+}
+
+pub fn render(widget: &Widget, events: &[Event]) {
+    let board = &widget.board;
+
+    for event in events {
+        match event {
+            Event::Revealed(position, mines_around) => {
+                board.set(position, Look::Revealed(*mines_around))
+            }
+            Event::Exploded(position) => board.set(position, Look::Exploded),
+            Event::MineShown(position) => board.set(position, Look::Mine),
+            Event::WrongFlag(position) => board.set(position, Look::WrongFlag),
+            Event::Flagged(position) => board.set(position, Look::Flagged),
+            Event::Questioned(position) => board.set(position, Look::Questioned),
+            Event::Unflagged(position)
+            | Event::Unquestioned(position)
+            | Event::Hidden(position) => board.set(position, Look::Hidden),
+            Event::MinesLeft(mines_left) => {
+                widget
+                    .label_mines_left
+                    .set_label(&mines_left_label(*mines_left));
+            }
+            Event::Cleared(cleared) => widget.label_mines_left.set_label(&cleared.to_string()),
+            Event::Resized(width, height) => board.resize(*width, *height),
+            Event::Shifted(x, y) => board.shift(*x, *y),
+            Event::Playing => {
+                clear_classes!(widget.button_reset.get_style_context(), "state_");
+                board.set_covered(false);
+            }
+            Event::Paused => {
+                widget
+                    .button_reset
+                    .get_style_context()
+                    .add_class("state_paused");
+                board.set_covered(true);
+            }
+            Event::Resumed => {
+                widget
+                    .button_reset
+                    .get_style_context()
+                    .remove_class("state_paused");
+                board.set_covered(false);
+            }
+            Event::Won => widget
+                .button_reset
+                .get_style_context()
+                .add_class("state_won"),
+            Event::Lost => widget
+                .button_reset
+                .get_style_context()
+                .add_class("state_lost"),
+        }
+    }
+}
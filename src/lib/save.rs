@@ -0,0 +1,257 @@
+//! Saved games are plain text, one `key value` pair per line, followed by the board:
+//!
+//! ```text
+//! ms-roj-save 1
+//! size 9 9
+//! mines 10
+//! seed 1234
+//! elapsed 8250
+//! assisted 0
+//! board
+//! --1*....
+//! ...
+//! ```
+//!
+//! The first line names the format and its version; files with a newer version are rejected.
+//! `elapsed` is the time played in milliseconds. `board` is followed by one line per row, top to
+//! bottom, with one character per cell:
+//!
+//! | cell     | safe | mine |
+//! |----------|------|------|
+//! | hidden   | `.`  | `*`  |
+//! | flagged  | `f`  | `F`  |
+//! | question | `q`  | `Q`  |
+//! | revealed | `-`  | `X`  |
+//!
+//! A board without any mines has not had its first reveal yet, so mines are placed on load as
+//! for a new game. A game still under way loads paused, so its clock only runs again once the
+//! player resumes it.
+
+use crate::lib::{
+    clock::Clock,
+    game::{check_board, Game},
+    grid::Grid,
+    position::Position,
+    state::State,
+};
+use std::{str::FromStr, time::Duration};
+
+pub const SAVE_FORMAT: &str = "ms-roj-save";
+pub const SAVE_VERSION: u32 = 1;
+
+/// Parses a value of `line`, rejecting anything that doesn't fit its type.
+fn value<T: FromStr>(line: &str, part: Option<&str>) -> Result<T, String> {
+    part.and_then(|part| part.parse().ok())
+        .ok_or_else(|| format!("Invalid line {:?}", line))
+}
+
+impl Game {
+    pub fn save(&self) -> String {
+        let mut lines = vec![
+            format!("{} {}", SAVE_FORMAT, SAVE_VERSION),
+            format!("size {} {}", self.width, self.height),
+            format!("mines {}", self.mine_count),
+            format!("seed {}", self.seed),
+            format!("elapsed {}", self.elapsed().as_millis()),
+            format!("assisted {}", self.assisted as u8),
+            "board".to_string(),
+        ];
+
+        for y in 0..self.height {
+            let row = (0..self.width)
+                .map(|x| match self.field.get(&Position(x, y)) {
+                    Some(field) => match (field.is_mine, field.is_clicked) {
+                        (false, true) => '-',
+                        (true, true) => 'X',
+                        (false, false) if field.is_flagged => 'f',
+                        (true, false) if field.is_flagged => 'F',
+                        (false, false) if field.is_question => 'q',
+                        (true, false) if field.is_question => 'Q',
+                        (false, false) => '.',
+                        (true, false) => '*',
+                    },
+                    None => '.',
+                })
+                .collect::<String>();
+
+            lines.push(row);
+        }
+
+        lines.join("\n") + "\n"
+    }
+
+    pub fn load(&mut self, text: &str) -> Result<(), String> {
+        let mut lines = text.lines();
+
+        let header = lines.next().unwrap_or("");
+        let version = header
+            .strip_prefix(SAVE_FORMAT)
+            .and_then(|version| version.trim().parse::<u32>().ok())
+            .ok_or_else(|| "Not a saved game".to_string())?;
+
+        if version > SAVE_VERSION {
+            return Err(format!("Saved game version {} is not supported", version));
+        }
+
+        let (mut size, mut mine_count, mut seed, mut elapsed, mut assisted) =
+            (None, None, None, 0, false);
+
+        for line in &mut lines {
+            let mut parts = line.split_whitespace();
+
+            match parts.next() {
+                Some("size") => {
+                    size = Some((value(line, parts.next())?, value(line, parts.next())?))
+                }
+                Some("mines") => mine_count = Some(value(line, parts.next())?),
+                Some("seed") => seed = Some(value(line, parts.next())?),
+                Some("elapsed") => elapsed = value(line, parts.next())?,
+                Some("assisted") => assisted = value::<u64>(line, parts.next())? != 0,
+                Some("board") => break,
+                Some(_) | None => {}
+            }
+        }
+
+        let (width, height) = size.ok_or_else(|| "Missing board size".to_string())?;
+        let mine_count = mine_count.ok_or_else(|| "Missing mine count".to_string())?;
+        check_board(width, height, mine_count)?;
+
+        let rows = lines
+            .take(height as usize)
+            .map(|row| row.trim().chars().collect::<Vec<char>>())
+            .collect::<Vec<Vec<char>>>();
+
+        if rows.len() != height as usize || rows.iter().any(|row| row.len() != width as usize) {
+            return Err(format!("The board must be {}×{} cells", width, height));
+        }
+
+        let mut mines = vec![];
+        for (y, row) in rows.iter().enumerate() {
+            for (x, cell) in row.iter().enumerate() {
+                match cell {
+                    '*' | 'F' | 'Q' | 'X' => mines.push(Position(x as u16, y as u16)),
+                    '.' | 'f' | 'q' | '-' => {}
+                    _ => return Err(format!("Unknown cell {:?}", cell)),
+                }
+            }
+        }
+
+        if !mines.is_empty() && mines.len() != mine_count as usize {
+            return Err(format!(
+                "The board holds {} mines, expected {}",
+                mines.len(),
+                mine_count
+            ));
+        }
+
+        let mut field = Grid::new(width, height, &mines);
+        for (y, row) in rows.iter().enumerate() {
+            for (x, cell) in row.iter().enumerate() {
+                if let Some(field) = field.get_mut(&Position(x as u16, y as u16)) {
+                    field.is_clicked = *cell == '-' || *cell == 'X';
+                    field.is_flagged = *cell == 'f' || *cell == 'F';
+                    field.is_question = *cell == 'q' || *cell == 'Q';
+                }
+            }
+        }
+
+        self.width = width;
+        self.height = height;
+        self.mine_count = mine_count;
+        self.seed = seed.unwrap_or(self.seed);
+        self.mines_placed = !mines.is_empty();
+        self.mines = mines;
+        self.hidden = field.values().filter(|field| !field.is_clicked).count();
+        self.flags = field.values().filter(|field| field.is_flagged).count() as u16;
+        self.field = field;
+        self.assisted = assisted;
+        self.no_guess_failed = false;
+        self.endless = None;
+        self.history.clear();
+
+        let exploded = rows.iter().any(|row| row.contains(&'X'));
+        let started = elapsed != 0
+            || self
+                .field
+                .values()
+                .any(|field| field.is_clicked || field.is_flagged || field.is_question);
+
+        self.state = if exploded {
+            State::Lost
+        } else if self.mines_placed && self.safe_left() == 0 {
+            State::Won
+        } else if started {
+            State::Paused
+        } else {
+            State::NotStarted
+        };
+
+        self.clock = Clock::new(Duration::from_millis(elapsed));
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn saved_games_load_back_the_same() {
+        let mut game = Game::new();
+        game.new_mines_from_seed(42);
+        game.reveal(Position(4, 4));
+        let flag = game
+            .field
+            .positions()
+            .find(|position| !game.field[position].is_clicked)
+            .expect("a hidden cell");
+        game.toggle_flag(flag);
+
+        let mut loaded = Game::new();
+        loaded.load(&game.save()).expect("the save loads");
+
+        assert_eq!(loaded.field, game.field);
+        assert_eq!(
+            (loaded.width, loaded.height, loaded.mine_count, loaded.seed),
+            (game.width, game.height, game.mine_count, game.seed)
+        );
+        assert_eq!((loaded.hidden, loaded.flags), (game.hidden, game.flags));
+        assert_eq!(loaded.state, State::Paused);
+
+        let elapsed = loaded.clock.elapsed();
+        std::thread::sleep(Duration::from_millis(10));
+        assert_eq!(loaded.clock.elapsed(), elapsed);
+    }
+
+    #[test]
+    fn paused_games_load_paused() {
+        let mut game = Game::new();
+        game.new_mines_from_seed(42);
+        game.reveal(Position(4, 4));
+        game.toggle_pause();
+
+        let mut loaded = Game::new();
+        loaded.load(&game.save()).expect("the save loads");
+
+        assert_eq!(loaded.state, State::Paused);
+    }
+
+    #[test]
+    fn boards_without_mines_wait_for_the_first_reveal() {
+        let mut game = Game::new();
+        game.load(&Game::new().save()).expect("the save loads");
+
+        assert!(!game.mines_placed);
+        assert_eq!(game.state, State::NotStarted);
+    }
+
+    #[test]
+    fn sizes_that_overflow_are_rejected() {
+        let save = Game::new().save();
+
+        for (from, to) in &[("size 9 9", "size 65538 9"), ("mines 10", "mines 65546")] {
+            assert!(Game::new().load(&save.replace(from, to)).is_err());
+        }
+    }
+}
@@ -0,0 +1,48 @@
+use gtk::{prelude::*, ApplicationWindow, Dialog, DialogFlags, Entry, Grid, Label, ResponseType};
+
+pub fn seed_dialog(window: &ApplicationWindow, seed: u64) -> Option<u64> {
+    let dialog = Dialog::with_buttons(
+        Some("New Game from Seed"),
+        Some(window),
+        DialogFlags::MODAL | DialogFlags::DESTROY_WITH_PARENT,
+        &[
+            ("Cancel", ResponseType::Cancel),
+            ("New Game", ResponseType::Ok),
+        ],
+    );
+    dialog.set_default_response(ResponseType::Ok);
+
+    let grid = Grid::new();
+    grid.set_row_spacing(8);
+    grid.set_column_spacing(8);
+    grid.set_border_width(8);
+
+    let entry_seed = Entry::new();
+    entry_seed.set_text(&seed.to_string());
+    entry_seed.set_activates_default(true);
+
+    let label_error = Label::new(None);
+    label_error.get_style_context().add_class("error");
+
+    grid.attach(&Label::new(Some("Seed")), 0, 0, 1, 1);
+    grid.attach(&entry_seed, 1, 0, 1, 1);
+    grid.attach(&label_error, 0, 1, 2, 1);
+
+    dialog.get_content_area().add(&grid);
+    dialog.show_all();
+
+    let seed = loop {
+        if dialog.run() != ResponseType::Ok {
+            break None;
+        }
+
+        match entry_seed.get_text().trim().parse() {
+            Ok(seed) => break Some(seed),
+            Err(_) => label_error.set_label("The seed must be a positive number"),
+        }
+    };
+
+    unsafe { dialog.destroy() }
+
+    seed
+}
@@ -0,0 +1,221 @@
+use crate::lib::{game::Game, gen_mines, grid::Grid, position::Position};
+use std::{
+    collections::HashSet,
+    sync::atomic::{AtomicBool, Ordering},
+    time::{Duration, Instant},
+};
+
+/// Boards tried before giving up on a guess-free one. Counting them rather than only timing the
+/// search lets a seed and first click give the same board on any machine that gets through them.
+pub const NO_GUESS_ATTEMPTS: u32 = 1000;
+/// The longest a search may take, for boards so large that the attempts don't fit in it.
+pub const NO_GUESS_TIMEOUT: Duration = Duration::from_secs(5);
+
+struct Constraint {
+    cells: HashSet<Position>,
+    mines: u16,
+}
+
+/// Plays the board from `start` using only deductions that never require a guess.
+pub fn is_solvable(mine_count: u16, field: &Grid, start: Position) -> bool {
+    let safe_total = field.len() - mine_count as usize;
+    let mut revealed: HashSet<Position> = HashSet::new();
+    let mut mines: HashSet<Position> = HashSet::new();
+    let mut queue = vec![start];
+
+    loop {
+        while let Some(position) = queue.pop() {
+            if !revealed.insert(position) {
+                continue;
+            }
+
+            let block = &field[&position];
+            if block.is_mine {
+                return false;
+            }
+
+            if block.mines_around == 0 {
+                queue.extend(
+                    field
+                        .neighbours(position)
+                        .filter(|position| !revealed.contains(position)),
+                );
+            }
+        }
+
+        if revealed.len() == safe_total {
+            return true;
+        }
+
+        let constraints = revealed
+            .iter()
+            .filter_map(|position| {
+                let mut cells = HashSet::new();
+                let mut flagged = 0;
+
+                for position in field.neighbours(*position) {
+                    if mines.contains(&position) {
+                        flagged += 1;
+                    } else if !revealed.contains(&position) {
+                        cells.insert(position);
+                    }
+                }
+
+                if cells.is_empty() {
+                    None
+                } else {
+                    Some(Constraint {
+                        cells,
+                        mines: field[position].mines_around - flagged,
+                    })
+                }
+            })
+            .collect::<Vec<Constraint>>();
+
+        let (mut safe, mut found) = (HashSet::new(), HashSet::new());
+
+        for constraint in &constraints {
+            if constraint.mines == 0 {
+                safe.extend(constraint.cells.iter().copied());
+            } else if constraint.mines as usize == constraint.cells.len() {
+                found.extend(constraint.cells.iter().copied());
+            }
+        }
+
+        if safe.is_empty() && found.is_empty() {
+            for a in &constraints {
+                for b in &constraints {
+                    if a.cells.len() >= b.cells.len() || !a.cells.is_subset(&b.cells) {
+                        continue;
+                    }
+
+                    let rest = b.cells.difference(&a.cells);
+                    let rest_mines = b.mines - a.mines;
+
+                    if rest_mines == 0 {
+                        safe.extend(rest.copied());
+                    } else if rest_mines as usize == b.cells.len() - a.cells.len() {
+                        found.extend(rest.copied());
+                    }
+                }
+            }
+        }
+
+        // The global count: the mines left are either all found or fill every unknown cell, on
+        // the whole board or outside any one constraint, which holds exactly its own mines.
+        if safe.is_empty() && found.is_empty() {
+            let unknown = field
+                .positions()
+                .filter(|position| !revealed.contains(position) && !mines.contains(position))
+                .collect::<HashSet<Position>>();
+            let left = (mine_count as usize).saturating_sub(mines.len());
+
+            let outside = constraints.iter().filter_map(|constraint| {
+                let left = left.checked_sub(constraint.mines as usize)?;
+                let cells = unknown
+                    .difference(&constraint.cells)
+                    .copied()
+                    .collect::<HashSet<Position>>();
+
+                Some((cells, left))
+            });
+
+            for (cells, left) in Some((unknown.clone(), left)).into_iter().chain(outside) {
+                if left == 0 {
+                    safe.extend(cells);
+                } else if left == cells.len() {
+                    found.extend(cells);
+                }
+            }
+        }
+
+        if safe.is_empty() && found.is_empty() {
+            return false;
+        }
+
+        mines.extend(found);
+        queue.extend(safe);
+    }
+}
+
+/// Retries generation with seeds derived from the game's seed until the solver clears the board
+/// from `start`, giving up after `NO_GUESS_ATTEMPTS` boards, once `NO_GUESS_TIMEOUT` has passed
+/// or as soon as `cancel` is set.
+pub fn gen_no_guess<F: FnMut(u32)>(
+    game: &Game,
+    exclude: &[Position],
+    start: Position,
+    cancel: &AtomicBool,
+    mut progress: F,
+) -> Option<Vec<Position>> {
+    let (width, height, mine_count) = (game.width, game.height, game.mine_count);
+    let deadline = Instant::now() + NO_GUESS_TIMEOUT;
+
+    for attempt in 0..NO_GUESS_ATTEMPTS {
+        if cancel.load(Ordering::Relaxed) || Instant::now() >= deadline {
+            break;
+        }
+
+        let mines = gen_mines(
+            width,
+            height,
+            mine_count,
+            game.seed
+                .wrapping_add((attempt as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)),
+            exclude,
+        );
+        let field = Grid::new(width, height, &mines);
+
+        if is_solvable(mine_count, &field, start) {
+            return Some(mines);
+        }
+
+        progress(attempt + 1);
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solvable(width: u16, height: u16, mines: &[Position], start: Position) -> bool {
+        is_solvable(mines.len() as u16, &Grid::new(width, height, mines), start)
+    }
+
+    #[test]
+    fn clears_boards_that_open_up_from_the_start() {
+        assert!(solvable(3, 3, &[Position(0, 0)], Position(2, 2)));
+    }
+
+    #[test]
+    fn counts_the_mines_left_over_the_whole_board() {
+        // the mines on the right can only be told apart knowing there are two of them
+        let mines = [Position(2, 1), Position(3, 1)];
+
+        assert!(solvable(4, 3, &mines, Position(0, 0)));
+    }
+
+    #[test]
+    fn gives_up_where_a_guess_is_needed() {
+        // the 1 in the corner could be any of the other three cells
+        assert!(!solvable(2, 2, &[Position(0, 0)], Position(1, 1)));
+        // a 50/50 between the two cells at the end of the row
+        assert!(!solvable(4, 2, &[Position(3, 0)], Position(0, 0)));
+        assert!(!solvable(3, 3, &[Position(0, 0)], Position(0, 0)));
+    }
+
+    #[test]
+    fn stops_searching_once_cancelled() {
+        let mut game = Game::new();
+        game.new_mines_from_seed(0);
+
+        let mut attempts = 0;
+        let mines = gen_no_guess(&game, &[], Position(0, 0), &AtomicBool::new(true), |_| {
+            attempts += 1
+        });
+
+        assert_eq!((mines, attempts), (None, 0));
+    }
+}
@@ -0,0 +1,27 @@
+/// Where a game is in its lifecycle. A game starts with its first reveal or flag, can be paused
+/// while running, and ends once it is won or lost.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum State {
+    NotStarted,
+    Running,
+    Paused,
+    Won,
+    Lost,
+}
+
+impl State {
+    /// Whether the board accepts moves.
+    pub fn is_playable(self) -> bool {
+        matches!(self, State::NotStarted | State::Running)
+    }
+
+    pub fn is_over(self) -> bool {
+        matches!(self, State::Won | State::Lost)
+    }
+}
+
+impl Default for State {
+    fn default() -> Self {
+        State::NotStarted
+    }
+}
@@ -1,12 +1,20 @@
-use crate::lib::widget::Widget;
+use crate::lib::{
+    bindings::fixed_accels,
+    board::Board,
+    difficulty::DIFFICULTIES,
+    first_click::{FirstClick, FIRST_CLICKS},
+    minimap::Minimap,
+    widget::Widget,
+};
 
 use gio::prelude::*;
+use glib::ToVariant;
 use gtk::{
     prelude::*, Application, ApplicationWindow, ApplicationWindowExt, CssProvider, Orientation,
     StyleContext,
 };
 
-use std::{cell::RefCell, collections::HashMap, rc::Rc};
+use std::{collections::HashMap, rc::Rc};
 
 macro_rules! action {
     ($application:expr, $menu_bar_actions:expr, $action_id:expr) => {
@@ -16,6 +24,14 @@ macro_rules! action {
     };
 }
 
+macro_rules! stateful_action {
+    ($application:expr, $menu_bar_actions:expr, $action_id:expr, $parameter:expr, $state:expr) => {
+        let action = gio::SimpleAction::new_stateful($action_id, $parameter, &$state.to_variant());
+        $application.add_action(&action);
+        $menu_bar_actions.insert($action_id.to_string(), Rc::new(action));
+    };
+}
+
 fn init_menu_bar_actions<'a>(
     application: &'a Application,
     window: &'a ApplicationWindow,
@@ -24,23 +40,72 @@ fn init_menu_bar_actions<'a>(
     let mut menu_bar_actions = HashMap::new();
 
     menu.append(Some("New Game"), Some("app.new_game"));
+    menu.append(Some("Open Game…"), Some("app.open_game"));
+    menu.append(Some("Save Game…"), Some("app.save_game"));
+    menu.append(Some("Pause"), Some("app.pause"));
     menu.append(Some("Quit"), Some("app.quit"));
-    menu.append(Some("Game 1"), Some("app.game_1"));
-    menu.append(Some("Game 2"), Some("app.game_2"));
-    menu.append(Some("Game 3"), Some("app.game_3"));
-
-    application.set_accels_for_action("app.quit", &["<Primary>Q"]);
-    application.set_accels_for_action("app.new_game", &["<Primary>N"]);
-    application.set_accels_for_action("app.game_1", &["<Primary>1"]);
-    application.set_accels_for_action("app.game_2", &["<Primary>2"]);
-    application.set_accels_for_action("app.game_3", &["<Primary>3"]);
+    menu.append(Some("Undo"), Some("app.undo"));
+    menu.append(Some("Redo"), Some("app.redo"));
+    menu.append(Some("Zoom In"), Some("app.zoom_in"));
+    menu.append(Some("Zoom Out"), Some("app.zoom_out"));
+    menu.append(Some("Reset Zoom"), Some("app.zoom_reset"));
+    for difficulty in DIFFICULTIES.iter() {
+        menu.append(
+            Some(difficulty.name),
+            Some(&format!("app.{}", difficulty.id)),
+        );
+    }
+    menu.append(Some("Custom…"), Some("app.custom"));
+    menu.append(Some("Endless"), Some("app.endless"));
+    menu.append(Some("New Game from Seed…"), Some("app.new_game_from_seed"));
+
+    let first_click_menu = gio::Menu::new();
+    for first_click in FIRST_CLICKS.iter() {
+        first_click_menu.append(
+            Some(first_click.name()),
+            Some(&format!("app.first_click::{}", first_click.id())),
+        );
+    }
+    menu.append_submenu(Some("First Click"), &first_click_menu);
+    menu.append(Some("No Guessing"), Some("app.no_guess"));
+    menu.append(Some("Question Marks"), Some("app.question_marks"));
+    menu.append(Some("Show Tenths"), Some("app.tenths"));
+    menu.append(Some("Preferences…"), Some("app.preferences"));
+
+    for (id, _, accels) in fixed_accels() {
+        let accels = accels.iter().map(String::as_str).collect::<Vec<&str>>();
+
+        application.set_accels_for_action(&format!("app.{}", id), &accels);
+    }
     application.set_app_menu(Some(&menu));
 
     action!(application, menu_bar_actions, "quit");
     action!(application, menu_bar_actions, "new_game");
-    action!(application, menu_bar_actions, "game_1");
-    action!(application, menu_bar_actions, "game_2");
-    action!(application, menu_bar_actions, "game_3");
+    action!(application, menu_bar_actions, "open_game");
+    action!(application, menu_bar_actions, "save_game");
+    action!(application, menu_bar_actions, "pause");
+    action!(application, menu_bar_actions, "undo");
+    action!(application, menu_bar_actions, "redo");
+    action!(application, menu_bar_actions, "zoom_in");
+    action!(application, menu_bar_actions, "zoom_out");
+    action!(application, menu_bar_actions, "zoom_reset");
+    action!(application, menu_bar_actions, "preferences");
+    for difficulty in DIFFICULTIES.iter() {
+        action!(application, menu_bar_actions, difficulty.id);
+    }
+    action!(application, menu_bar_actions, "custom");
+    action!(application, menu_bar_actions, "new_game_from_seed");
+    stateful_action!(
+        application,
+        menu_bar_actions,
+        "first_click",
+        Some(glib::VariantTy::new("s").unwrap()),
+        FirstClick::SafeOpening.id()
+    );
+    stateful_action!(application, menu_bar_actions, "endless", None, false);
+    stateful_action!(application, menu_bar_actions, "no_guess", None, false);
+    stateful_action!(application, menu_bar_actions, "question_marks", None, false);
+    stateful_action!(application, menu_bar_actions, "tenths", None, false);
 
     menu_bar_actions
 }
@@ -70,7 +135,7 @@ pub fn build_ui<'a>(application: &'a Application) -> Widget {
     let label_mines_left_box = gtk::Box::new(Orientation::Horizontal, 0);
     label_mines_left.set_visible(true);
     label_mines_left.set_can_focus(false);
-    label_mines_left.set_label("10");
+    label_mines_left.set_label("010");
     label_mines_left.get_style_context().add_class("label");
     label_mines_left.set_size_request(50, 0);
     label_mines_left.set_hexpand(true);
@@ -116,15 +181,38 @@ pub fn build_ui<'a>(application: &'a Application) -> Widget {
     // #-- end top bar
 
     // #-- mines
-    let mines_grid = gtk::Grid::new();
-    mines_grid.get_style_context().add_class("mines");
-    mines_grid.hide_on_delete();
-    let mut mines = HashMap::new();
+    let board = Board::new();
+    let board_box = gtk::Box::new(Orientation::Vertical, 0);
+    board_box.get_style_context().add_class("mines");
+    board_box.set_halign(gtk::Align::Center);
+    board_box.set_valign(gtk::Align::Center);
+    board_box.add(&board.area);
+    board.view.add(&board_box);
+
+    let minimap = Minimap::new();
+    let board_overlay = gtk::Overlay::new();
+    board_overlay.add(&board.view);
+    board_overlay.add_overlay(&minimap.area);
 
     main_widget.add(&top_bar);
     main_widget.set_child_packing(&top_bar, false, true, 0, gtk::PackType::Start);
-    main_widget.add(&mines_grid);
-    main_widget.set_child_packing(&mines_grid, true, true, 0, gtk::PackType::Start);
+    main_widget.add(&board_overlay);
+    main_widget.set_child_packing(&board_overlay, true, true, 0, gtk::PackType::Start);
+    // #-- end mines
+
+    // #-- seed
+    let label_seed = gtk::Label::new(None);
+    label_seed.set_visible(true);
+    // Selectable labels take focus, which would keep keys from reaching the board; the seed can
+    // still be selected with the mouse and copied from the context menu.
+    label_seed.set_selectable(true);
+    label_seed.set_can_focus(false);
+    label_seed.set_halign(gtk::Align::Start);
+    label_seed.get_style_context().add_class("seed");
+
+    main_widget.add(&label_seed);
+    main_widget.set_child_packing(&label_seed, false, true, 0, gtk::PackType::Start);
+    // #-- end seed
 
     let menu_bar_actions = init_menu_bar_actions(&application, &window);
 
@@ -143,11 +231,12 @@ pub fn build_ui<'a>(application: &'a Application) -> Widget {
     window.show_all();
 
     Widget {
-        mines: Rc::new(RefCell::new(mines)),
-        mines_grid: Rc::new(RefCell::new(mines_grid)),
+        board,
+        minimap,
         window,
         label_mines_left,
         label_time,
+        label_seed,
         button_reset,
         menu_bar_actions,
     }
@@ -1,15 +1,16 @@
-use crate::lib::{block::Block, position::Position};
+use crate::lib::{board::Board, minimap::Minimap};
 use gio::SimpleAction;
-use gtk::{ApplicationWindow, Button, Grid, Label};
-use std::{cell::RefCell, collections::HashMap, rc::Rc};
+use gtk::{ApplicationWindow, Button, Label};
+use std::{collections::HashMap, rc::Rc};
 
 #[derive(Clone, Debug)]
 pub struct Widget {
     pub window: ApplicationWindow,
-    pub mines: Rc<RefCell<HashMap<Position, Block>>>,
-    pub mines_grid: Rc<RefCell<Grid>>,
+    pub board: Board,
+    pub minimap: Minimap,
     pub label_mines_left: Label,
     pub label_time: Label,
+    pub label_seed: Label,
     pub button_reset: Button,
     pub menu_bar_actions: HashMap<String, Rc<SimpleAction>>,
 }
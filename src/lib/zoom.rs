@@ -0,0 +1,78 @@
+//! Zoom levels are remembered per difficulty in a plain text file, one `difficulty zoom` pair
+//! per line. Custom boards share a single level, as do endless ones.
+
+use crate::lib::game::Game;
+use std::{collections::HashMap, fs, io, path::PathBuf};
+
+pub fn zoom_path() -> Option<PathBuf> {
+    glib::get_user_config_dir().map(|dir| dir.join("ms-roj").join("zoom"))
+}
+
+pub fn zoom_key(game: &Game) -> &'static str {
+    if game.endless.is_some() {
+        return "endless";
+    }
+
+    game.difficulty()
+        .map_or("custom", |difficulty| difficulty.id)
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ZoomLevels {
+    levels: HashMap<String, f64>,
+}
+
+impl ZoomLevels {
+    pub fn load() -> Self {
+        let text = zoom_path()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .unwrap_or_default();
+
+        let levels = text
+            .lines()
+            .filter_map(|line| {
+                let mut parts = line.split_whitespace();
+                let key = parts.next()?;
+                let zoom = parts.next()?.parse::<f64>().ok()?;
+
+                if zoom.is_finite() && zoom > 0.0 {
+                    Some((key.to_string(), zoom))
+                } else {
+                    None
+                }
+            })
+            .collect();
+
+        Self { levels }
+    }
+
+    pub fn get(&self, key: &str) -> Option<f64> {
+        self.levels.get(key).copied()
+    }
+
+    /// Remembers the zoom for `key`, or forgets it when `None`, and writes the levels out.
+    pub fn set(&mut self, key: &str, zoom: Option<f64>) -> io::Result<()> {
+        match zoom {
+            Some(zoom) => self.levels.insert(key.to_string(), zoom),
+            None => self.levels.remove(key),
+        };
+
+        let path = match zoom_path() {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let mut lines = self
+            .levels
+            .iter()
+            .map(|(key, zoom)| format!("{} {}\n", key, zoom))
+            .collect::<Vec<String>>();
+        lines.sort();
+
+        fs::write(path, lines.concat())
+    }
+}
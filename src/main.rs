@@ -1,4 +1,3 @@
-#![feature(label_break_value)]
 #![allow(unused_variables, unused_mut, dead_code)]
 
 macro_rules! clear_classes {
@@ -11,40 +10,69 @@ macro_rules! clear_classes {
     };
 }
 
+extern crate cairo;
 extern crate gdk;
 extern crate gio;
 extern crate glib;
 extern crate gtk;
+extern crate pango;
 extern crate rand;
+extern crate rand_chacha;
 
 mod lib;
 
 use gio::prelude::*;
 use lib::application::Application;
-use std::{cell::RefCell, env::args};
+use std::{cell::RefCell, env::args, process};
+
+fn take_seed(args: &mut Vec<String>) -> Option<u64> {
+    let index = args
+        .iter()
+        .position(|arg| arg == "--seed" || arg.starts_with("--seed="))?;
+
+    let arg = args.remove(index);
+    let value = match arg.strip_prefix("--seed=") {
+        Some(value) => value.to_string(),
+        None if index < args.len() => args.remove(index),
+        None => String::new(),
+    };
+
+    match value.parse() {
+        Ok(seed) => Some(seed),
+        Err(_) => {
+            eprintln!("--seed expects a positive number, got {:?}", value);
+            process::exit(1);
+        }
+    }
+}
 
 fn main() {
+    let mut args = args().collect::<Vec<_>>();
+
+    let seed = take_seed(&mut args);
+
     let application = gtk::Application::new(Some("com.github.ankjevel.ms-roj"), Default::default())
         .expect("Initialization failed...");
 
-    application.connect_startup(|app| {
+    application.connect_startup(move |app| {
         let res_bytes = include_bytes!("../resources/resources.gresource");
         let data = glib::Bytes::from(&res_bytes[..]);
         let resource = gio::Resource::from_data(&data).unwrap();
         gio::resources_register(&resource);
 
-        let application = Application::new(app);
+        let application = Application::new(app, seed);
         let application_container = RefCell::new(Some(application));
         app.connect_shutdown(move |_| {
             let application = application_container
                 .borrow_mut()
                 .take()
                 .expect("Shutdown called multiple times");
+            application.autosave();
             drop(application);
         });
     });
 
     application.connect_activate(|_| {});
 
-    application.run(&args().collect::<Vec<_>>());
+    application.run(&args);
 }